@@ -0,0 +1,66 @@
+use std::num::NonZeroU32;
+
+use avltriee::{
+    Avltriee, AvltrieeAllocator, AvltrieeSearch, AvltrieeUpdate, PackedAvltrieeLayout,
+    SplitAvltrieeAllocator, VecAvltrieeAllocator,
+};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const ROWS: u32 = 100_000;
+
+fn values() -> Vec<u32> {
+    let mut rng = StdRng::seed_from_u64(0);
+    (0..ROWS).map(|_| rng.gen_range(0..ROWS / 4)).collect()
+}
+
+fn build<A: AvltrieeAllocator<u32> + Default>(values: &[u32]) -> Avltriee<u32, u32, A> {
+    let mut t = Avltriee::with_allocator(A::default());
+    for (i, value) in values.iter().enumerate() {
+        t.update(NonZeroU32::new(i as u32 + 1).unwrap(), value);
+    }
+    t
+}
+
+fn bench_layout<A: AvltrieeAllocator<u32> + Default>(c: &mut Criterion, name: &str) {
+    let values = values();
+    let mut group = c.benchmark_group("layout");
+
+    group.bench_function(BenchmarkId::new("insert", name), |b| {
+        b.iter(|| build::<A>(black_box(&values)))
+    });
+
+    let t = build::<A>(&values);
+    eprintln!(
+        "layout/{name}: {:.1} bytes per row",
+        t.stats().allocated_bytes as f64 / ROWS as f64
+    );
+    group.bench_function(BenchmarkId::new("ge", name), |b| {
+        b.iter(|| {
+            for value in values.iter().step_by(16) {
+                black_box(t.ge(value));
+            }
+        })
+    });
+    group.bench_function(BenchmarkId::new("iter", name), |b| {
+        b.iter(|| {
+            t.iter()
+                .map(|row| *t.value(row).unwrap() as u64)
+                .sum::<u64>()
+        })
+    });
+    group.finish();
+}
+
+fn layout(c: &mut Criterion) {
+    bench_layout::<VecAvltrieeAllocator<u32>>(c, "wide");
+    bench_layout::<VecAvltrieeAllocator<u32, NonZeroU32, PackedAvltrieeLayout>>(c, "packed");
+    bench_layout::<SplitAvltrieeAllocator<u32>>(c, "split");
+    bench_layout::<SplitAvltrieeAllocator<u32, NonZeroU32, PackedAvltrieeLayout>>(
+        c,
+        "split_packed",
+    );
+}
+
+criterion_group!(benches, layout);
+criterion_main!(benches);
@@ -0,0 +1,215 @@
+use std::{collections::BTreeMap, num::NonZeroU32};
+
+use avltriee::{Avltriee, AvltrieeUpdate};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+const ROWS: u32 = 100_000;
+
+/// BTreeMap keeping the rows of each value, with the value of each row for updates and deletes.
+#[derive(Default)]
+struct BTreeIndex {
+    map: BTreeMap<u32, Vec<u32>>,
+    values: Vec<Option<u32>>,
+}
+
+impl BTreeIndex {
+    fn update(&mut self, row: u32, value: u32) {
+        self.delete(row);
+        if self.values.len() <= row as usize {
+            self.values.resize(row as usize + 1, None);
+        }
+        self.values[row as usize] = Some(value);
+        self.map.entry(value).or_default().push(row);
+    }
+
+    fn delete(&mut self, row: u32) {
+        if let Some(value) = self.values.get_mut(row as usize).and_then(Option::take) {
+            let rows = self.map.get_mut(&value).unwrap();
+            rows.swap_remove(rows.iter().position(|&r| r == row).unwrap());
+            if rows.is_empty() {
+                self.map.remove(&value);
+            }
+        }
+    }
+}
+
+fn row(i: u32) -> NonZeroU32 {
+    NonZeroU32::new(i).unwrap()
+}
+
+fn build_triee(values: &[u32]) -> Avltriee<u32> {
+    let mut t = Avltriee::new();
+    for (i, value) in values.iter().enumerate() {
+        t.update(row(i as u32 + 1), value);
+    }
+    t
+}
+
+fn build_btree(values: &[u32]) -> BTreeIndex {
+    let mut b = BTreeIndex::default();
+    for (i, &value) in values.iter().enumerate() {
+        b.update(i as u32 + 1, value);
+    }
+    b
+}
+
+fn datasets() -> Vec<(&'static str, Vec<u32>)> {
+    let mut rng = StdRng::seed_from_u64(0);
+    vec![
+        ("sequential", (0..ROWS).collect()),
+        (
+            "random",
+            (0..ROWS).map(|_| rng.gen_range(0..ROWS)).collect(),
+        ),
+        (
+            "duplicates",
+            (0..ROWS).map(|_| rng.gen_range(0..16)).collect(),
+        ),
+    ]
+}
+
+fn insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("insert");
+    for (name, values) in datasets() {
+        group.bench_with_input(BenchmarkId::new("avltriee", name), &values, |b, values| {
+            b.iter(|| build_triee(black_box(values)))
+        });
+        group.bench_with_input(BenchmarkId::new("btreemap", name), &values, |b, values| {
+            b.iter(|| build_btree(black_box(values)))
+        });
+    }
+    group.finish();
+}
+
+fn update(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(1);
+    let moves: Vec<(u32, u32)> = (0..ROWS / 10)
+        .map(|_| (rng.gen_range(1..=ROWS), rng.gen_range(0..ROWS)))
+        .collect();
+
+    let mut group = c.benchmark_group("update");
+    for (name, values) in datasets() {
+        group.bench_function(BenchmarkId::new("avltriee", name), |b| {
+            b.iter_batched(
+                || build_triee(&values),
+                |mut t| {
+                    for &(i, value) in &moves {
+                        t.update(row(i), &value);
+                    }
+                    t
+                },
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new("btreemap", name), |b| {
+            b.iter_batched(
+                || build_btree(&values),
+                |mut m| {
+                    for &(i, value) in &moves {
+                        m.update(i, value);
+                    }
+                    m
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn range(c: &mut Criterion) {
+    let mut group = c.benchmark_group("range");
+    for (name, values) in datasets() {
+        let max = *values.iter().max().unwrap();
+        let (start, end) = (max / 4, max / 4 + max / 10);
+
+        let t = build_triee(&values);
+        group.bench_function(BenchmarkId::new("avltriee", name), |b| {
+            b.iter(|| t.iter_range(black_box(&start), black_box(&end)).count())
+        });
+
+        let m = build_btree(&values);
+        group.bench_function(BenchmarkId::new("btreemap", name), |b| {
+            b.iter(|| {
+                m.map
+                    .range(black_box(start)..=black_box(end))
+                    .flat_map(|(_, rows)| rows.iter())
+                    .count()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn offset(c: &mut Criterion) {
+    let offsets: Vec<u32> = (0..ROWS).step_by(997).collect();
+
+    let mut group = c.benchmark_group("offset");
+    for (name, values) in datasets() {
+        let t = build_triee(&values);
+        group.bench_function(BenchmarkId::new("avltriee", name), |b| {
+            b.iter(|| {
+                offsets
+                    .iter()
+                    .map(|&offset| t.iter_from_offset(black_box(offset)).take(10).count())
+                    .sum::<usize>()
+            })
+        });
+
+        let m = build_btree(&values);
+        group.bench_function(BenchmarkId::new("btreemap", name), |b| {
+            b.iter(|| {
+                offsets
+                    .iter()
+                    .map(|&offset| {
+                        m.map
+                            .values()
+                            .flat_map(|rows| rows.iter())
+                            .skip(black_box(offset) as usize)
+                            .take(10)
+                            .count()
+                    })
+                    .sum::<usize>()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn delete(c: &mut Criterion) {
+    let mut rows: Vec<u32> = (1..=ROWS).collect();
+    rows.shuffle(&mut StdRng::seed_from_u64(2));
+
+    let mut group = c.benchmark_group("delete");
+    for (name, values) in datasets() {
+        group.bench_function(BenchmarkId::new("avltriee", name), |b| {
+            b.iter_batched(
+                || build_triee(&values),
+                |mut t| {
+                    for &i in &rows {
+                        t.delete(row(i));
+                    }
+                    t
+                },
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new("btreemap", name), |b| {
+            b.iter_batched(
+                || build_btree(&values),
+                |mut m| {
+                    for &i in &rows {
+                        m.delete(i);
+                    }
+                    m
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, insert, update, range, offset, delete);
+criterion_main!(benches);
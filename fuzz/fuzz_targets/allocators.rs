@@ -0,0 +1,45 @@
+#![no_main]
+
+use std::num::{NonZeroU16, NonZeroU32};
+
+use arbitrary::Arbitrary;
+use avltriee::{
+    Avltriee, AvltrieeAllocator, AvltrieeRow, AvltrieeSearch, AvltrieeUpdate,
+    ChunkedAvltrieeAllocator, PackedAvltrieeLayout, SplitAvltrieeAllocator,
+};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    Update { row: u8, value: u8 },
+    Delete(u8),
+}
+
+/// Runs the operations with the specified allocator and returns the rows in ascending order.
+fn run<A: AvltrieeAllocator<u8, R>, R: AvltrieeRow>(allocator: A, ops: &[Op]) -> Vec<usize> {
+    let mut t = Avltriee::with_allocator(allocator);
+    for op in ops {
+        match *op {
+            Op::Update { row, value } => {
+                t.update(R::from_index(row as usize + 1).unwrap(), &(value % 16))
+            }
+            Op::Delete(row) => t.delete(R::from_index(row as usize + 1).unwrap()),
+        }
+        t.validate().unwrap();
+    }
+    t.iter().map(|row| row.index()).collect()
+}
+
+fuzz_target!(|ops: Vec<Op>| {
+    let split = run(SplitAvltrieeAllocator::<u8>::new(), &ops);
+    let packed = run(
+        SplitAvltrieeAllocator::<u8, NonZeroU16, PackedAvltrieeLayout>::new(),
+        &ops,
+    );
+    let chunked = run(
+        ChunkedAvltrieeAllocator::<u8, 16, NonZeroU32, PackedAvltrieeLayout>::new(),
+        &ops,
+    );
+    assert_eq!(split, packed);
+    assert_eq!(split, chunked);
+});
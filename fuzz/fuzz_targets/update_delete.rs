@@ -0,0 +1,48 @@
+#![no_main]
+
+use std::num::NonZeroU32;
+
+use arbitrary::Arbitrary;
+use avltriee::{Avltriee, AvltrieeSearch, AvltrieeUpdate};
+use libfuzzer_sys::fuzz_target;
+
+/// Few distinct values, so that most updates and deletes go through the same branch.
+#[derive(Arbitrary, Debug)]
+enum Op {
+    Insert(u8),
+    Update { row: u8, value: u8 },
+    Delete(u8),
+    Rebuild,
+}
+
+fuzz_target!(|ops: Vec<Op>| {
+    let mut t: Avltriee<u8> = Avltriee::new();
+    let mut values: Vec<Option<u8>> = vec![None; 257];
+
+    for op in ops {
+        match op {
+            Op::Insert(value) => {
+                if t.rows_count() < 256 {
+                    let row = t.insert(&(value % 16));
+                    values[row.get() as usize] = Some(value % 16);
+                }
+            }
+            Op::Update { row, value } => {
+                let row = row as u32 + 1;
+                t.update(NonZeroU32::new(row).unwrap(), &(value % 16));
+                values[row as usize] = Some(value % 16);
+            }
+            Op::Delete(row) => {
+                let row = row as u32 + 1;
+                t.delete(NonZeroU32::new(row).unwrap());
+                values[row as usize] = None;
+            }
+            Op::Rebuild => t.rebuild(),
+        }
+        t.validate().unwrap();
+
+        for (row, value) in values.iter().enumerate().skip(1) {
+            assert_eq!(t.value(NonZeroU32::new(row as u32).unwrap()), value.as_ref());
+        }
+    }
+});
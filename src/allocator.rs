@@ -1,43 +1,316 @@
-use std::num::NonZeroU32;
+use std::{
+    mem::size_of,
+    num::NonZeroU32,
+    ptr::{addr_of, addr_of_mut},
+};
 
-use crate::AvltrieeNode;
+use crate::{AvltrieeLayout, AvltrieeNode, AvltrieeRow, WideAvltrieeLayout};
 
-pub trait AvltrieeAllocator<T> {
-    fn as_ptr(&self) -> *const AvltrieeNode<T>;
-    fn as_mut_ptr(&mut self) -> *mut AvltrieeNode<T>;
+pub trait AvltrieeAllocator<T, R: AvltrieeRow = NonZeroU32> {
+    type Layout: AvltrieeLayout<R>;
 
-    fn get(&self, row: NonZeroU32) -> Option<&AvltrieeNode<T>>;
+    /// Value held inside the node. T if values are stored with the links, () if stored separately.
+    type NodeValue;
 
-    fn resize(&mut self, rows_count: u32);
+    /// Returns the pointer to the node at the specified index. Index 0 holds the head of the triee.
+    /// # Safety
+    /// index must not exceed the rows_count given to the last resize.
+    unsafe fn node_ptr(
+        &self,
+        index: usize,
+    ) -> *const AvltrieeNode<Self::NodeValue, R, Self::Layout>;
+
+    /// Returns the mutable pointer to the node at the specified index. Index 0 holds the head of the triee.
+    /// # Safety
+    /// index must not exceed the rows_count given to the last resize.
+    unsafe fn node_mut_ptr(
+        &mut self,
+        index: usize,
+    ) -> *mut AvltrieeNode<Self::NodeValue, R, Self::Layout>;
+
+    fn get(&self, row: R) -> Option<&AvltrieeNode<Self::NodeValue, R, Self::Layout>>;
+
+    /// Returns the pointer to the value at the specified index.
+    /// # Safety
+    /// index must not exceed the rows_count given to the last resize.
+    unsafe fn value_ptr(&self, index: usize) -> *const T;
+
+    /// Returns the mutable pointer to the value at the specified index.
+    /// # Safety
+    /// index must not exceed the rows_count given to the last resize.
+    unsafe fn value_mut_ptr(&mut self, index: usize) -> *mut T;
+
+    fn resize(&mut self, rows_count: usize);
+
+    /// Returns the same links the layout keeps outside of the nodes.
+    fn same_table(&self) -> &<Self::Layout as AvltrieeLayout<R>>::SameTable;
+
+    fn same_table_mut(&mut self) -> &mut <Self::Layout as AvltrieeLayout<R>>::SameTable;
+
+    /// Returns the bytes reserved for nodes, values and the same table. Heap memory owned by the values is not included.
+    fn allocated_bytes(&self) -> usize;
 }
 
-pub struct VecAvltrieeAllocator<T> {
-    node_list: Vec<AvltrieeNode<T>>,
+pub struct VecAvltrieeAllocator<
+    T,
+    R: AvltrieeRow = NonZeroU32,
+    L: AvltrieeLayout<R> = WideAvltrieeLayout,
+> {
+    node_list: Vec<AvltrieeNode<T, R, L>>,
+    same_table: L::SameTable,
 }
 
-impl<T: Default + Clone> AvltrieeAllocator<T> for VecAvltrieeAllocator<T> {
-    fn as_ptr(&self) -> *const AvltrieeNode<T> {
-        self.node_list.as_ptr()
+impl<T: Default + Clone, R: AvltrieeRow, L: AvltrieeLayout<R>> AvltrieeAllocator<T, R>
+    for VecAvltrieeAllocator<T, R, L>
+{
+    type Layout = L;
+    type NodeValue = T;
+
+    unsafe fn node_ptr(
+        &self,
+        index: usize,
+    ) -> *const AvltrieeNode<Self::NodeValue, R, Self::Layout> {
+        self.node_list.as_ptr().add(index)
+    }
+
+    unsafe fn node_mut_ptr(
+        &mut self,
+        index: usize,
+    ) -> *mut AvltrieeNode<Self::NodeValue, R, Self::Layout> {
+        self.node_list.as_mut_ptr().add(index)
     }
 
-    fn as_mut_ptr(&mut self) -> *mut AvltrieeNode<T> {
-        self.node_list.as_mut_ptr()
+    fn get(&self, row: R) -> Option<&AvltrieeNode<Self::NodeValue, R, Self::Layout>> {
+        self.node_list.get(row.index())
     }
 
-    fn get(&self, row: NonZeroU32) -> Option<&AvltrieeNode<T>> {
-        self.node_list.get(row.get() as usize)
+    unsafe fn value_ptr(&self, index: usize) -> *const T {
+        addr_of!((*self.node_ptr(index)).value)
     }
 
-    fn resize(&mut self, rows_count: u32) {
-        self.node_list
-            .resize(rows_count as usize + 1, Default::default())
+    unsafe fn value_mut_ptr(&mut self, index: usize) -> *mut T {
+        addr_of_mut!((*self.node_mut_ptr(index)).value)
+    }
+
+    fn resize(&mut self, rows_count: usize) {
+        self.node_list.resize(rows_count + 1, Default::default())
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        self.node_list.capacity() * size_of::<AvltrieeNode<T, R, L>>()
+            + L::same_table_bytes(&self.same_table)
+    }
+
+    fn same_table(&self) -> &L::SameTable {
+        &self.same_table
+    }
+
+    fn same_table_mut(&mut self) -> &mut L::SameTable {
+        &mut self.same_table
     }
 }
 
-impl<T: Default> VecAvltrieeAllocator<T> {
+impl<T: Default, R: AvltrieeRow, L: AvltrieeLayout<R>> VecAvltrieeAllocator<T, R, L> {
     pub fn new() -> Self {
         VecAvltrieeAllocator {
             node_list: vec![Default::default()],
+            same_table: Default::default(),
         }
     }
 }
+
+impl<T: Default, R: AvltrieeRow, L: AvltrieeLayout<R>> Default for VecAvltrieeAllocator<T, R, L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Allocator that stores nodes in fixed-size chunks.
+/// Growing never moves existing nodes, so their addresses stay stable.
+pub struct ChunkedAvltrieeAllocator<
+    T,
+    const CHUNK_SIZE: usize = 4096,
+    R: AvltrieeRow = NonZeroU32,
+    L: AvltrieeLayout<R> = WideAvltrieeLayout,
+> {
+    chunks: Vec<Box<[AvltrieeNode<T, R, L>]>>,
+    len: usize,
+    same_table: L::SameTable,
+}
+
+impl<T: Default, const CHUNK_SIZE: usize, R: AvltrieeRow, L: AvltrieeLayout<R>>
+    ChunkedAvltrieeAllocator<T, CHUNK_SIZE, R, L>
+{
+    pub fn new() -> Self {
+        const { assert!(CHUNK_SIZE > 0, "CHUNK_SIZE must not be zero") };
+        let mut allocator = ChunkedAvltrieeAllocator {
+            chunks: vec![],
+            len: 0,
+            same_table: Default::default(),
+        };
+        allocator.push_chunk();
+        allocator
+    }
+
+    fn push_chunk(&mut self) {
+        self.chunks
+            .push((0..CHUNK_SIZE).map(|_| Default::default()).collect());
+    }
+}
+
+impl<T: Default, const CHUNK_SIZE: usize, R: AvltrieeRow, L: AvltrieeLayout<R>> Default
+    for ChunkedAvltrieeAllocator<T, CHUNK_SIZE, R, L>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Default, const CHUNK_SIZE: usize, R: AvltrieeRow, L: AvltrieeLayout<R>>
+    AvltrieeAllocator<T, R> for ChunkedAvltrieeAllocator<T, CHUNK_SIZE, R, L>
+{
+    type Layout = L;
+    type NodeValue = T;
+
+    unsafe fn node_ptr(
+        &self,
+        index: usize,
+    ) -> *const AvltrieeNode<Self::NodeValue, R, Self::Layout> {
+        self.chunks
+            .get_unchecked(index / CHUNK_SIZE)
+            .as_ptr()
+            .add(index % CHUNK_SIZE)
+    }
+
+    unsafe fn node_mut_ptr(
+        &mut self,
+        index: usize,
+    ) -> *mut AvltrieeNode<Self::NodeValue, R, Self::Layout> {
+        self.chunks
+            .get_unchecked_mut(index / CHUNK_SIZE)
+            .as_mut_ptr()
+            .add(index % CHUNK_SIZE)
+    }
+
+    fn get(&self, row: R) -> Option<&AvltrieeNode<Self::NodeValue, R, Self::Layout>> {
+        (row.index() <= self.len).then(|| unsafe { &*self.node_ptr(row.index()) })
+    }
+
+    unsafe fn value_ptr(&self, index: usize) -> *const T {
+        addr_of!((*self.node_ptr(index)).value)
+    }
+
+    unsafe fn value_mut_ptr(&mut self, index: usize) -> *mut T {
+        addr_of_mut!((*self.node_mut_ptr(index)).value)
+    }
+
+    fn resize(&mut self, rows_count: usize) {
+        let chunks_count = rows_count / CHUNK_SIZE + 1;
+        if rows_count > self.len {
+            // Slots left over from a shrink may hold stale nodes. New chunks are already default.
+            let existing = self.chunks.len() * CHUNK_SIZE;
+            for index in self.len + 1..=rows_count.min(existing - 1) {
+                unsafe { *self.node_mut_ptr(index) = Default::default() };
+            }
+            while self.chunks.len() < chunks_count {
+                self.push_chunk();
+            }
+        } else {
+            self.chunks.truncate(chunks_count);
+        }
+        self.len = rows_count;
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        self.chunks.len() * CHUNK_SIZE * size_of::<AvltrieeNode<T, R, L>>()
+            + L::same_table_bytes(&self.same_table)
+    }
+
+    fn same_table(&self) -> &L::SameTable {
+        &self.same_table
+    }
+
+    fn same_table_mut(&mut self) -> &mut L::SameTable {
+        &mut self.same_table
+    }
+}
+
+/// Allocator that stores values and links in separate arrays.
+/// Descending the triee reads only the links and the compared values, so more of them stay in cache.
+pub struct SplitAvltrieeAllocator<
+    T,
+    R: AvltrieeRow = NonZeroU32,
+    L: AvltrieeLayout<R> = WideAvltrieeLayout,
+> {
+    node_list: Vec<AvltrieeNode<(), R, L>>,
+    value_list: Vec<T>,
+    same_table: L::SameTable,
+}
+
+impl<T: Default + Clone, R: AvltrieeRow, L: AvltrieeLayout<R>> AvltrieeAllocator<T, R>
+    for SplitAvltrieeAllocator<T, R, L>
+{
+    type Layout = L;
+    type NodeValue = ();
+
+    unsafe fn node_ptr(
+        &self,
+        index: usize,
+    ) -> *const AvltrieeNode<Self::NodeValue, R, Self::Layout> {
+        self.node_list.as_ptr().add(index)
+    }
+
+    unsafe fn node_mut_ptr(
+        &mut self,
+        index: usize,
+    ) -> *mut AvltrieeNode<Self::NodeValue, R, Self::Layout> {
+        self.node_list.as_mut_ptr().add(index)
+    }
+
+    fn get(&self, row: R) -> Option<&AvltrieeNode<Self::NodeValue, R, Self::Layout>> {
+        self.node_list.get(row.index())
+    }
+
+    unsafe fn value_ptr(&self, index: usize) -> *const T {
+        self.value_list.as_ptr().add(index)
+    }
+
+    unsafe fn value_mut_ptr(&mut self, index: usize) -> *mut T {
+        self.value_list.as_mut_ptr().add(index)
+    }
+
+    fn resize(&mut self, rows_count: usize) {
+        self.node_list.resize(rows_count + 1, Default::default());
+        self.value_list.resize(rows_count + 1, Default::default());
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        self.node_list.capacity() * size_of::<AvltrieeNode<(), R, L>>()
+            + self.value_list.capacity() * size_of::<T>()
+            + L::same_table_bytes(&self.same_table)
+    }
+
+    fn same_table(&self) -> &L::SameTable {
+        &self.same_table
+    }
+
+    fn same_table_mut(&mut self) -> &mut L::SameTable {
+        &mut self.same_table
+    }
+}
+
+impl<T: Default, R: AvltrieeRow, L: AvltrieeLayout<R>> SplitAvltrieeAllocator<T, R, L> {
+    pub fn new() -> Self {
+        SplitAvltrieeAllocator {
+            node_list: vec![Default::default()],
+            value_list: vec![Default::default()],
+            same_table: Default::default(),
+        }
+    }
+}
+
+impl<T: Default, R: AvltrieeRow, L: AvltrieeLayout<R>> Default for SplitAvltrieeAllocator<T, R, L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,42 +1,52 @@
-use std::{cmp::Ordering, num::NonZeroU32};
+use std::cmp::Ordering;
 
-use crate::{Avltriee, AvltrieeAllocator, AvltrieeNode, AvltrieeSearch, AvltrieeUpdate};
+use crate::{
+    Avltriee, AvltrieeAllocator, AvltrieeNode, AvltrieeRow, AvltrieeSearch, AvltrieeUpdate,
+};
 
-impl<T: Ord + Clone, A: AvltrieeAllocator<T>> AvltrieeSearch<T, T, A> for Avltriee<T, T, A> {
+impl<T: Ord + Clone, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> AvltrieeSearch<T, T, A, R>
+    for Avltriee<T, T, A, R>
+{
     fn cmp(left: &T, right: &T) -> Ordering {
         left.cmp(right)
     }
 
     /// Returns the value of the specified row. Returns None if the row does not exist.
-    fn value(&self, row: NonZeroU32) -> Option<&T> {
-        Some(self.as_ref().node(row)?)
+    fn value(&self, row: R) -> Option<&T> {
+        self.as_ref()
+            .node(row)
+            .map(|_| unsafe { self.as_ref().stored_value_unchecked(row) })
     }
 
     /// Returns the value of the specified row.
-    unsafe fn value_unchecked(&self, row: NonZeroU32) -> &T {
-        self.as_ref().node_unchecked(row)
+    unsafe fn value_unchecked(&self, row: R) -> &T {
+        self.as_ref().stored_value_unchecked(row)
     }
 
     /// Returns node and value of the specified row.
-    unsafe fn node_value_unchecked(&self, row: NonZeroU32) -> (&AvltrieeNode<T>, &T) {
-        let node = self.as_ref().node_unchecked(row);
-        (node, node)
+    unsafe fn node_value_unchecked(
+        &self,
+        row: R,
+    ) -> (&AvltrieeNode<A::NodeValue, R, A::Layout>, &T) {
+        (self.node_unchecked(row), self.stored_value_unchecked(row))
     }
 }
 
-impl<T: Ord + Clone, A: AvltrieeAllocator<T>> AvltrieeUpdate<T, T, A> for Avltriee<T, T, A> {
+impl<T: Ord + Clone, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> AvltrieeUpdate<T, T, A, R>
+    for Avltriee<T, T, A, R>
+{
     fn convert_on_insert_unique(&mut self, input: &T) -> T {
         input.clone()
     }
 }
 
-impl<T, I: ?Sized, A> AsRef<Avltriee<T, I, A>> for Avltriee<T, I, A> {
-    fn as_ref(&self) -> &Avltriee<T, I, A> {
+impl<T, I: ?Sized, A, R> AsRef<Avltriee<T, I, A, R>> for Avltriee<T, I, A, R> {
+    fn as_ref(&self) -> &Avltriee<T, I, A, R> {
         self
     }
 }
-impl<T, I: ?Sized, A> AsMut<Avltriee<T, I, A>> for Avltriee<T, I, A> {
-    fn as_mut(&mut self) -> &mut Avltriee<T, I, A> {
+impl<T, I: ?Sized, A, R> AsMut<Avltriee<T, I, A, R>> for Avltriee<T, I, A, R> {
+    fn as_mut(&mut self) -> &mut Avltriee<T, I, A, R> {
         self
     }
 }
@@ -0,0 +1,34 @@
+use std::{cmp::Ordering, num::NonZeroU32};
+
+use crate::{row::count_index, Avltriee, AvltrieeAllocator, AvltrieeRow, AvltrieeSearch};
+
+/// Difference of a row between two triees.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AvltrieeDiff<R = NonZeroU32> {
+    /// The row exists only in the other triee.
+    Added(R),
+    /// The row exists only in this triee.
+    Removed(R),
+    /// The row exists in both triees with different values.
+    Changed(R),
+}
+
+impl<T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> Avltriee<T, I, A, R> {
+    /// Compares with another triee over the same rows and generates an iterator of differences in row order.
+    pub fn diff<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = AvltrieeDiff<R>> + 'a
+    where
+        Self: AvltrieeSearch<T, I, A, R>,
+    {
+        let rows_count = count_index(std::cmp::max(self.rows_count(), other.rows_count()));
+        (1..=rows_count).filter_map(move |row| {
+            let row = R::from_index(row)?;
+            match (self.value(row), other.value(row)) {
+                (None, Some(_)) => Some(AvltrieeDiff::Added(row)),
+                (Some(_), None) => Some(AvltrieeDiff::Removed(row)),
+                (Some(left), Some(right)) => (Self::cmp(left, right) != Ordering::Equal)
+                    .then_some(AvltrieeDiff::Changed(row)),
+                (None, None) => None,
+            }
+        })
+    }
+}
@@ -0,0 +1,112 @@
+use std::fmt::Write;
+
+use crate::{Avltriee, AvltrieeAllocator, AvltrieeRow};
+
+impl<T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> Avltriee<T, I, A, R> {
+    /// Renders the triee in Graphviz dot format for debugging.
+    /// Each node shows its row, value, height and balance factor. Rows of the same value are drawn as a dashed third branch.
+    pub fn to_dot<F: Fn(&T) -> String>(&self, format_value: F) -> String {
+        let mut dot = String::from("digraph avltriee {\n    node [shape=box];\n");
+        if let Some(root) = self.root() {
+            self.dot_node(&mut dot, root, &format_value);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn dot_node<F: Fn(&T) -> String>(&self, dot: &mut String, row: R, format_value: &F) {
+        let node = unsafe { self.node_unchecked(row) };
+        let _ = writeln!(
+            dot,
+            "    {row} [label=\"{row}: {}\\nh={} bf={}\"];",
+            escape(&format_value(unsafe { self.stored_value_unchecked(row) })),
+            node.height(),
+            self.balance_factor(row)
+        );
+        for (child, label) in [(node.left, "L"), (node.right, "R")] {
+            if let Some(child) = child {
+                let _ = writeln!(dot, "    {row} -> {child} [label=\"{label}\"];");
+                self.dot_node(dot, child, format_value);
+            }
+        }
+
+        let mut prev = row;
+        let mut same = unsafe { self.same_unchecked(row) };
+        while let Some(same_row) = same {
+            let _ = writeln!(
+                dot,
+                "    {same_row} [label=\"{same_row}\", style=dashed];\n    {prev} -> {same_row} [style=dashed];"
+            );
+            prev = same_row;
+            same = unsafe { self.same_unchecked(same_row) };
+        }
+    }
+
+    /// Renders the triee as an indented text tree for debugging.
+    /// Children are marked L and R, and the rows of the same value are listed on an S branch.
+    pub fn to_ascii<F: Fn(&T) -> String>(&self, format_value: F) -> String {
+        let mut ascii = String::new();
+        if let Some(root) = self.root() {
+            self.ascii_node(&mut ascii, root, "", "", &format_value);
+        }
+        ascii
+    }
+
+    fn ascii_node<F: Fn(&T) -> String>(
+        &self,
+        ascii: &mut String,
+        row: R,
+        branch: &str,
+        indent: &str,
+        format_value: &F,
+    ) {
+        let node = unsafe { self.node_unchecked(row) };
+        let _ = writeln!(
+            ascii,
+            "{branch}{row}: {} (h={} bf={})",
+            format_value(unsafe { self.stored_value_unchecked(row) }),
+            node.height(),
+            self.balance_factor(row)
+        );
+
+        let mut same = vec![];
+        let mut next = unsafe { self.same_unchecked(row) };
+        while let Some(same_row) = next {
+            same.push(same_row.to_string());
+            next = unsafe { self.same_unchecked(same_row) };
+        }
+
+        let children: Vec<(&str, R)> = [("L", node.left), ("R", node.right)]
+            .into_iter()
+            .filter_map(|(label, child)| child.map(|child| (label, child)))
+            .collect();
+        let branches = children.len() + usize::from(!same.is_empty());
+        for (i, (label, child)) in children.into_iter().enumerate() {
+            let last = i + 1 == branches;
+            self.ascii_node(
+                ascii,
+                child,
+                &format!("{indent}{}{label} ", if last { "└─" } else { "├─" }),
+                &format!("{indent}{}", if last { "   " } else { "│  " }),
+                format_value,
+            );
+        }
+        if !same.is_empty() {
+            let _ = writeln!(ascii, "{indent}└─S {}", same.join(", "));
+        }
+    }
+
+    fn balance_factor(&self, row: R) -> i16 {
+        let node = unsafe { self.node_unchecked(row) };
+        let height = |child: Option<R>| {
+            child.map_or(0, |child| {
+                unsafe { self.node_unchecked(child) }.height() as i16
+            })
+        };
+        height(node.left) - height(node.right)
+    }
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
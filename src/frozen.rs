@@ -0,0 +1,269 @@
+use std::{cmp::Ordering, num::NonZeroU32, ops::Range, slice};
+
+use crate::{iter::Order, Avltriee, AvltrieeAllocator, AvltrieeRow};
+
+/// Immutable read-optimized copy of a triee.
+/// Distinct values are stored in Eytzinger order, so a search touches the array from the front and stays in cache.
+/// Rows keep their original numbers.
+pub struct AvltrieeFrozen<T, R = NonZeroU32> {
+    /// Distinct values in Eytzinger order.
+    values: Vec<T>,
+    /// Sorted rank of the value at each Eytzinger position.
+    ranks: Vec<u32>,
+    /// Start of the rows of each rank. Has one more entry for the end.
+    offsets: Vec<u32>,
+    /// Rows in ascending order of value.
+    rows: Vec<R>,
+    /// Eytzinger position (1-based) of the value of each row. 0 if the row does not exist.
+    row_values: Vec<u32>,
+}
+
+impl<T: Ord + Clone, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> Avltriee<T, T, A, R> {
+    /// Creates an immutable read-optimized copy of the triee.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the triee has more than [u32::MAX] rows.
+    pub fn freeze(&self) -> AvltrieeFrozen<T, R> {
+        let mut sorted = vec![];
+        let mut offsets = vec![];
+        let mut rows = vec![];
+        for row in self.iter() {
+            let value = unsafe { self.stored_value_unchecked(row) };
+            if sorted
+                .last()
+                .is_none_or(|last: &Option<T>| last.as_ref().unwrap().cmp(value) != Ordering::Equal)
+            {
+                sorted.push(Some(value.clone()));
+                offsets.push(frozen_index(rows.len()));
+            }
+            rows.push(row);
+        }
+        offsets.push(frozen_index(rows.len()));
+
+        let mut ranks = vec![0; sorted.len()];
+        eytzinger(&mut ranks, 1, &mut 0);
+
+        let mut row_values = vec![0; rows.iter().map(|row| row.index()).max().unwrap_or(0) + 1];
+        for (position, &rank) in ranks.iter().enumerate() {
+            let rank = rank as usize;
+            for row in &rows[offsets[rank] as usize..offsets[rank + 1] as usize] {
+                row_values[row.index()] = frozen_index(position + 1);
+            }
+        }
+
+        AvltrieeFrozen {
+            values: ranks
+                .iter()
+                .map(|&rank| sorted[rank as usize].take().unwrap())
+                .collect(),
+            ranks,
+            offsets,
+            rows,
+            row_values,
+        }
+    }
+}
+
+/// Converts an index of a frozen triee to its stored width.
+fn frozen_index(index: usize) -> u32 {
+    u32::try_from(index).expect("too many rows to freeze")
+}
+
+/// Assigns sorted ranks to Eytzinger positions by in-order traversal.
+fn eytzinger(ranks: &mut [u32], position: usize, rank: &mut u32) {
+    if position <= ranks.len() {
+        eytzinger(ranks, position * 2, rank);
+        ranks[position - 1] = *rank;
+        *rank += 1;
+        eytzinger(ranks, position * 2 + 1, rank);
+    }
+}
+
+impl<T: Ord, R: AvltrieeRow> AvltrieeFrozen<T, R> {
+    /// Returns the number of rows.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns true if there are no rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Returns the value of the specified row. Returns None if the row does not exist.
+    pub fn value(&self, row: R) -> Option<&T> {
+        match self.row_values.get(row.index()) {
+            Some(&position) if position != 0 => Some(&self.values[position as usize - 1]),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of distinct values less than the specified value.
+    /// If or_equal is true, counts values equal to it as well.
+    fn rank(&self, value: &T, or_equal: bool) -> usize {
+        let mut position = 1;
+        while position <= self.values.len() {
+            let ord = self.values[position - 1].cmp(value);
+            position = position * 2
+                + usize::from(ord == Ordering::Less || or_equal && ord == Ordering::Equal);
+        }
+        position >>= position.trailing_ones() + 1;
+        if position == 0 {
+            self.values.len()
+        } else {
+            self.ranks[position - 1] as usize
+        }
+    }
+
+    /// Returns the first row of the value at the specified rank.
+    fn head(&self, rank: usize) -> Option<R> {
+        (rank < self.ranks.len()).then(|| self.rows[self.offsets[rank] as usize])
+    }
+
+    /// Search row of a value.
+    pub fn row(&self, value: &T) -> Option<R> {
+        self.ge(value)
+            .filter(|&row| self.value(row).unwrap().cmp(value) == Ordering::Equal)
+    }
+
+    /// Search >= value.
+    pub fn ge(&self, value: &T) -> Option<R> {
+        self.head(self.rank(value, false))
+    }
+
+    /// Search <= value.
+    pub fn le(&self, value: &T) -> Option<R> {
+        self.rank(value, true)
+            .checked_sub(1)
+            .and_then(|rank| self.head(rank))
+    }
+
+    /// Search > value.
+    pub fn gt(&self, value: &T) -> Option<R> {
+        self.head(self.rank(value, true))
+    }
+
+    /// Search < value.
+    pub fn lt(&self, value: &T) -> Option<R> {
+        self.rank(value, false)
+            .checked_sub(1)
+            .and_then(|rank| self.head(rank))
+    }
+
+    /// Search with range value.
+    pub fn range(&self, start_value: &T, end_value: &T) -> Option<Range<R>> {
+        let ranks = self.ranks_range(start_value, end_value);
+        (!ranks.is_empty()).then(|| Range {
+            start: self.rows[self.offsets[ranks.start] as usize],
+            end: self.rows[self.offsets[ranks.end - 1] as usize],
+        })
+    }
+
+    fn ranks_range(&self, start_value: &T, end_value: &T) -> Range<usize> {
+        let start = self.rank(start_value, false);
+        let end = self.rank(end_value, true);
+        start..std::cmp::max(start, end)
+    }
+
+    fn iter_ranks(&self, ranks: Range<usize>, order: Order) -> AvltrieeFrozenIter<'_, T, R> {
+        AvltrieeFrozenIter {
+            frozen: self,
+            ranks,
+            rows: [].iter(),
+            order,
+        }
+    }
+
+    /// Generate an iterator.
+    pub fn iter(&self) -> AvltrieeFrozenIter<'_, T, R> {
+        self.iter_ranks(0..self.ranks.len(), Order::Asc)
+    }
+
+    /// Generate an iterator. Iterates in descending order.
+    pub fn desc_iter(&self) -> AvltrieeFrozenIter<'_, T, R> {
+        self.iter_ranks(0..self.ranks.len(), Order::Desc)
+    }
+
+    /// Generates an iterator of rows with the same value as the specified value.
+    pub fn iter_by(&self, value: &T) -> AvltrieeFrozenIter<'_, T, R> {
+        self.iter_ranks(self.ranks_range(value, value), Order::Asc)
+    }
+
+    /// Generates an iterator with values starting from the specified value.
+    pub fn iter_from(&self, value: &T) -> AvltrieeFrozenIter<'_, T, R> {
+        self.iter_ranks(self.rank(value, false)..self.ranks.len(), Order::Asc)
+    }
+
+    /// Generates an iterator with values starting from the specified value. Iterates in descending order.
+    pub fn desc_iter_from(&self, value: &T) -> AvltrieeFrozenIter<'_, T, R> {
+        self.iter_ranks(self.rank(value, false)..self.ranks.len(), Order::Desc)
+    }
+
+    /// Generates an iterator of rows with values less than or equal to the specified value.
+    pub fn iter_to(&self, value: &T) -> AvltrieeFrozenIter<'_, T, R> {
+        self.iter_ranks(0..self.rank(value, true), Order::Asc)
+    }
+
+    /// Generates an iterator of rows with values less than or equal to the specified value. Iterates in descending order.
+    pub fn desc_iter_to(&self, value: &T) -> AvltrieeFrozenIter<'_, T, R> {
+        self.iter_ranks(0..self.rank(value, true), Order::Desc)
+    }
+
+    /// Generates an iterator of rows with values greater than the specified value.
+    pub fn iter_over(&self, value: &T) -> AvltrieeFrozenIter<'_, T, R> {
+        self.iter_ranks(self.rank(value, true)..self.ranks.len(), Order::Asc)
+    }
+
+    /// Generates an iterator of rows with values greater than the specified value. Iterates in descending order.
+    pub fn desc_iter_over(&self, value: &T) -> AvltrieeFrozenIter<'_, T, R> {
+        self.iter_ranks(self.rank(value, true)..self.ranks.len(), Order::Desc)
+    }
+
+    /// Generates an iterator of rows with values less than the specified value.
+    pub fn iter_under(&self, value: &T) -> AvltrieeFrozenIter<'_, T, R> {
+        self.iter_ranks(0..self.rank(value, false), Order::Asc)
+    }
+
+    /// Generates an iterator of rows with values less than the specified value. Iterates in descending order.
+    pub fn desc_iter_under(&self, value: &T) -> AvltrieeFrozenIter<'_, T, R> {
+        self.iter_ranks(0..self.rank(value, false), Order::Desc)
+    }
+
+    /// Generates an iterator of rows with the specified range of values.
+    pub fn iter_range(&self, start: &T, end: &T) -> AvltrieeFrozenIter<'_, T, R> {
+        self.iter_ranks(self.ranks_range(start, end), Order::Asc)
+    }
+
+    /// Generates an iterator of rows with the specified range of values. Iterates in descending order.
+    pub fn desc_iter_range(&self, start: &T, end: &T) -> AvltrieeFrozenIter<'_, T, R> {
+        self.iter_ranks(self.ranks_range(start, end), Order::Desc)
+    }
+}
+
+/// Iterator over the rows of an [AvltrieeFrozen].
+/// Rows having the same value are yielded in the same order as [crate::AvltrieeIter].
+pub struct AvltrieeFrozenIter<'a, T, R = NonZeroU32> {
+    frozen: &'a AvltrieeFrozen<T, R>,
+    ranks: Range<usize>,
+    rows: slice::Iter<'a, R>,
+    order: Order,
+}
+
+impl<'a, T, R: AvltrieeRow> Iterator for AvltrieeFrozenIter<'a, T, R> {
+    type Item = R;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(row) = self.rows.next() {
+                return Some(*row);
+            }
+            let rank = match self.order {
+                Order::Asc => self.ranks.next()?,
+                Order::Desc => self.ranks.next_back()?,
+            };
+            let offsets = &self.frozen.offsets;
+            self.rows = self.frozen.rows[offsets[rank] as usize..offsets[rank + 1] as usize].iter();
+        }
+    }
+}
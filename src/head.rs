@@ -1,35 +1,51 @@
-use std::num::NonZeroU32;
-
-use crate::{Avltriee, AvltrieeAllocator};
-
-pub(crate) struct AvltrieeHead {
-    root: Option<NonZeroU32>,
-    rows_count: u32,
+use crate::{Avltriee, AvltrieeAllocator, AvltrieeRow};
+
+/// Stored in place of the node at index 0, overlapping its parent, left and right links.
+#[repr(C)]
+pub(crate) struct AvltrieeHead<R: AvltrieeRow> {
+    root: Option<R>,
+    rows_count: R::Count,
+    len: R::Count,
 }
 
-impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
-    fn head(&self) -> &AvltrieeHead {
-        unsafe { &*(self.allocator.as_ptr() as *const AvltrieeHead) }
+impl<T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> Avltriee<T, I, A, R> {
+    fn head(&self) -> &AvltrieeHead<R> {
+        unsafe { &*(self.allocator.node_ptr(0) as *const AvltrieeHead<R>) }
     }
 
-    fn head_mut(&mut self) -> &mut AvltrieeHead {
-        unsafe { &mut *(self.allocator.as_mut_ptr() as *mut AvltrieeHead) }
+    fn head_mut(&mut self) -> &mut AvltrieeHead<R> {
+        unsafe { &mut *(self.allocator.node_mut_ptr(0) as *mut AvltrieeHead<R>) }
     }
 
-    pub(crate) fn set_root(&mut self, row: Option<NonZeroU32>) {
+    pub(crate) fn set_root(&mut self, row: Option<R>) {
         self.head_mut().root = row;
     }
 
-    pub(crate) fn root(&self) -> Option<NonZeroU32> {
+    pub(crate) fn root(&self) -> Option<R> {
         self.head().root
     }
 
-    pub(crate) fn set_rows_count(&mut self, len: u32) {
+    pub(crate) fn set_rows_count(&mut self, len: R::Count) {
         self.head_mut().rows_count = len;
     }
 
     /// Return count of rows.
-    pub fn rows_count(&self) -> u32 {
+    /// This is the highest allocated row, including rows that have been deleted.
+    pub fn rows_count(&self) -> R::Count {
         self.head().rows_count
     }
+
+    pub(crate) fn set_len(&mut self, len: R::Count) {
+        self.head_mut().len = len;
+    }
+
+    /// Returns the number of rows holding a value.
+    pub fn len(&self) -> R::Count {
+        self.head().len
+    }
+
+    /// Returns true if no row holds a value.
+    pub fn is_empty(&self) -> bool {
+        self.len() == R::Count::default()
+    }
 }
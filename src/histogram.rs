@@ -0,0 +1,74 @@
+use std::num::NonZeroU32;
+
+use crate::{
+    iter::Order,
+    row::{count_index, index_count},
+    Avltriee, AvltrieeAllocator, AvltrieeRow, AvltrieeSearch,
+};
+
+/// Bucket of an equi-depth histogram. Returned by [Avltriee::histogram].
+#[derive(Clone, Debug, PartialEq)]
+pub struct AvltrieeHistogramBucket<R: AvltrieeRow = NonZeroU32> {
+    /// Row with the lowest value of the bucket.
+    pub start: R,
+    /// Row with the highest value of the bucket.
+    pub end: R,
+    /// Number of rows in the bucket.
+    pub count: R::Count,
+}
+
+impl<T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> Avltriee<T, I, A, R> {
+    /// Returns the number of rows with the specified range of values in O(log n).
+    /// The count is exact, computed from the subtree counts.
+    pub fn estimate_range_count(&self, start: &I, end: &I) -> R::Count
+    where
+        Self: AvltrieeSearch<T, I, A, R>,
+    {
+        match self.range(start, end) {
+            Some(range) => {
+                self.rank(range.end, &Order::Asc) + self.same_count(range.end)
+                    - self.rank(range.start, &Order::Asc)
+            }
+            None => R::Count::default(),
+        }
+    }
+
+    /// Splits the rows into the specified number of buckets holding about the same number of rows.
+    /// Rows of the same value may be split across neighbouring buckets.
+    /// Returns fewer buckets if there are fewer rows than buckets.
+    pub fn histogram(&self, buckets: usize) -> Vec<AvltrieeHistogramBucket<R>> {
+        if buckets == 0 {
+            return vec![];
+        }
+        let len = count_index(self.len()) as u128;
+        let boundary = |bucket: usize| (len * bucket as u128 / buckets as u128) as usize;
+        (0..buckets)
+            .filter_map(|bucket| {
+                let (start, end) = (boundary(bucket), boundary(bucket + 1));
+                (start < end).then(|| AvltrieeHistogramBucket {
+                    start: self.select(index_count(start), &Order::Asc).0,
+                    end: self.select(index_count(end - 1), &Order::Asc).0,
+                    count: index_count(end - start),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the row at the specified quantile. q is between 0.0 and 1.0.
+    /// Uses the nearest-rank method, so 0.5 returns the lower median of an even number of rows.
+    /// Rows of the same value are counted individually.
+    /// Returns None if the triee is empty or q is out of range.
+    pub fn quantile(&self, q: f64) -> Option<R> {
+        if !(0.0..=1.0).contains(&q) || self.is_empty() {
+            return None;
+        }
+        let len = count_index(self.len());
+        let position = ((q * len as f64).ceil() as usize).clamp(1, len) - 1;
+        Some(self.select(index_count(position), &Order::Asc).0)
+    }
+
+    /// Returns the row at each of the specified quantiles. See [Avltriee::quantile].
+    pub fn quantiles(&self, qs: &[f64]) -> Vec<Option<R>> {
+        qs.iter().map(|&q| self.quantile(q)).collect()
+    }
+}
@@ -1,54 +1,59 @@
+mod distinct;
+mod join;
+mod offset;
+
 use std::{cmp::Ordering, num::NonZeroU32};
 
-use crate::{search::AvltrieeSearch, AvltrieeAllocator};
+use crate::{search::AvltrieeSearch, AvltrieeAllocator, AvltrieeRow};
 
 use super::Avltriee;
 
+pub use distinct::AvltrieeDistinctIter;
+pub use join::AvltrieeMergeJoin;
+
 #[derive(PartialEq)]
-enum Order {
+pub(crate) enum Order {
     Asc,
     Desc,
 }
 
-pub struct AvltrieeIter<'a, T, I: ?Sized, A> {
-    now: Option<NonZeroU32>,
-    end_row: Option<NonZeroU32>,
-    same_branch: Option<NonZeroU32>,
-    triee: &'a Avltriee<T, I, A>,
-    next_func: fn(
-        &Avltriee<T, I, A>,
-        NonZeroU32,
-        Option<NonZeroU32>,
-    ) -> Option<(NonZeroU32, Option<NonZeroU32>)>,
+type NextFunc<T, I, A, R> = fn(&Avltriee<T, I, A, R>, R, Option<R>) -> Option<(R, Option<R>)>;
+
+pub struct AvltrieeIter<'a, T, I: ?Sized, A, R = NonZeroU32> {
+    now: Option<R>,
+    end_row: Option<R>,
+    same_branch: Option<R>,
+    triee: &'a Avltriee<T, I, A, R>,
+    next_func: NextFunc<T, I, A, R>,
 }
 
-impl<'a, T, I: ?Sized, A: AvltrieeAllocator<T>> AvltrieeIter<'a, T, I, A> {
+impl<'a, T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> AvltrieeIter<'a, T, I, A, R> {
     fn new(
-        triee: &'a Avltriee<T, I, A>,
-        now: Option<NonZeroU32>,
-        end_row: Option<NonZeroU32>,
+        triee: &'a Avltriee<T, I, A, R>,
+        now: Option<R>,
+        end_row: Option<R>,
         order: Order,
-    ) -> AvltrieeIter<'a, T, I, A> {
+    ) -> AvltrieeIter<'a, T, I, A, R> {
         match order {
             Order::Asc => AvltrieeIter {
                 now,
                 end_row,
                 same_branch: None,
                 triee,
-                next_func: Avltriee::<T, I, A>::next,
+                next_func: Avltriee::<T, I, A, R>::next,
             },
             Order::Desc => AvltrieeIter {
                 now: end_row,
                 end_row: now,
                 same_branch: None,
                 triee,
-                next_func: Avltriee::<T, I, A>::next_desc,
+                next_func: Avltriee::<T, I, A, R>::next_desc,
             },
         }
     }
 
     /// Generates an iterator of nodes with the same value as the specified value.
-    pub fn by<S: AvltrieeSearch<T, I, A>>(s: &'a S, value: &I) -> AvltrieeIter<'a, T, I, A> {
+    pub fn by<S: AvltrieeSearch<T, I, A, R>>(s: &'a S, value: &I) -> AvltrieeIter<'a, T, I, A, R> {
         let triee = s.as_ref();
         let edge = s.edge(value);
         let row = if edge.1 == Ordering::Equal {
@@ -60,40 +65,52 @@ impl<'a, T, I: ?Sized, A: AvltrieeAllocator<T>> AvltrieeIter<'a, T, I, A> {
     }
 
     /// Generates an iterator that is greater than or equal to the specified value.
-    pub fn from_asc<S: AvltrieeSearch<T, I, A>>(s: &'a S, value: &I) -> AvltrieeIter<'a, T, I, A> {
+    pub fn from_asc<S: AvltrieeSearch<T, I, A, R>>(
+        s: &'a S,
+        value: &I,
+    ) -> AvltrieeIter<'a, T, I, A, R> {
         Self::from_inner(s, value, Order::Asc)
     }
 
     /// Generates a descending iterator with greater than or equal to the specified value.
-    pub fn from_desc<S: AvltrieeSearch<T, I, A>>(s: &'a S, value: &I) -> AvltrieeIter<'a, T, I, A> {
+    pub fn from_desc<S: AvltrieeSearch<T, I, A, R>>(
+        s: &'a S,
+        value: &I,
+    ) -> AvltrieeIter<'a, T, I, A, R> {
         Self::from_inner(s, value, Order::Desc)
     }
 
-    fn from_inner<S: AvltrieeSearch<T, I, A>>(
+    fn from_inner<S: AvltrieeSearch<T, I, A, R>>(
         s: &'a S,
         value: &I,
         order: Order,
-    ) -> AvltrieeIter<'a, T, I, A> {
+    ) -> AvltrieeIter<'a, T, I, A, R> {
         let triee = s.as_ref();
         let now = s.ge(value);
         AvltrieeIter::new(triee, now, now.and_then(|_| triee.max(triee.root())), order)
     }
 
     /// Generates an iterator of nodes with values ​​less than or equal to the specified value.
-    pub fn to_asc<S: AvltrieeSearch<T, I, A>>(s: &'a S, value: &I) -> AvltrieeIter<'a, T, I, A> {
+    pub fn to_asc<S: AvltrieeSearch<T, I, A, R>>(
+        s: &'a S,
+        value: &I,
+    ) -> AvltrieeIter<'a, T, I, A, R> {
         Self::to_inner(s, value, Order::Asc)
     }
 
     /// Generates an iterator of nodes with values ​​less than or equal to the specified value. Iterates in descending order.
-    pub fn to_desc<S: AvltrieeSearch<T, I, A>>(s: &'a S, value: &I) -> AvltrieeIter<'a, T, I, A> {
+    pub fn to_desc<S: AvltrieeSearch<T, I, A, R>>(
+        s: &'a S,
+        value: &I,
+    ) -> AvltrieeIter<'a, T, I, A, R> {
         Self::to_inner(s, value, Order::Desc)
     }
 
-    fn to_inner<S: AvltrieeSearch<T, I, A>>(
+    fn to_inner<S: AvltrieeSearch<T, I, A, R>>(
         s: &'a S,
         value: &I,
         order: Order,
-    ) -> AvltrieeIter<'a, T, I, A> {
+    ) -> AvltrieeIter<'a, T, I, A, R> {
         let triee = s.as_ref();
         let end_row = s.le(value);
         AvltrieeIter::new(
@@ -105,43 +122,52 @@ impl<'a, T, I: ?Sized, A: AvltrieeAllocator<T>> AvltrieeIter<'a, T, I, A> {
     }
 
     /// Generates an iterator of nodes with values ​​greater than the specified value.
-    pub fn over_asc<S: AvltrieeSearch<T, I, A>>(s: &'a S, value: &I) -> AvltrieeIter<'a, T, I, A> {
+    pub fn over_asc<S: AvltrieeSearch<T, I, A, R>>(
+        s: &'a S,
+        value: &I,
+    ) -> AvltrieeIter<'a, T, I, A, R> {
         Self::over_inner(s, value, Order::Asc)
     }
 
     /// Generates an iterator of nodes with values ​​greater than the specified value. Iterates in descending order.
-    pub fn over_desc<S: AvltrieeSearch<T, I, A>>(s: &'a S, value: &I) -> AvltrieeIter<'a, T, I, A> {
+    pub fn over_desc<S: AvltrieeSearch<T, I, A, R>>(
+        s: &'a S,
+        value: &I,
+    ) -> AvltrieeIter<'a, T, I, A, R> {
         Self::over_inner(s, value, Order::Desc)
     }
 
-    fn over_inner<S: AvltrieeSearch<T, I, A>>(
+    fn over_inner<S: AvltrieeSearch<T, I, A, R>>(
         s: &'a S,
         value: &I,
         order: Order,
-    ) -> AvltrieeIter<'a, T, I, A> {
+    ) -> AvltrieeIter<'a, T, I, A, R> {
         let triee = s.as_ref();
         let now = s.gt(value);
         AvltrieeIter::new(triee, now, now.and_then(|_| triee.max(triee.root())), order)
     }
 
     /// Generates an iterator of nodes with values ​​less than the specified value.
-    pub fn under_asc<S: AvltrieeSearch<T, I, A>>(s: &'a S, value: &I) -> AvltrieeIter<'a, T, I, A> {
+    pub fn under_asc<S: AvltrieeSearch<T, I, A, R>>(
+        s: &'a S,
+        value: &I,
+    ) -> AvltrieeIter<'a, T, I, A, R> {
         Self::under_inner(s, value, Order::Asc)
     }
 
     /// Generates an iterator of nodes with values ​​less than the specified value. Iterates in descending order.
-    pub fn under_desc<S: AvltrieeSearch<T, I, A>>(
+    pub fn under_desc<S: AvltrieeSearch<T, I, A, R>>(
         s: &'a S,
         value: &I,
-    ) -> AvltrieeIter<'a, T, I, A> {
+    ) -> AvltrieeIter<'a, T, I, A, R> {
         Self::under_inner(s, value, Order::Desc)
     }
 
-    fn under_inner<S: AvltrieeSearch<T, I, A>>(
+    fn under_inner<S: AvltrieeSearch<T, I, A, R>>(
         s: &'a S,
         value: &I,
         order: Order,
-    ) -> AvltrieeIter<'a, T, I, A> {
+    ) -> AvltrieeIter<'a, T, I, A, R> {
         let triee = s.as_ref();
         let end_row = s.lt(value);
         AvltrieeIter::new(
@@ -153,29 +179,29 @@ impl<'a, T, I: ?Sized, A: AvltrieeAllocator<T>> AvltrieeIter<'a, T, I, A> {
     }
 
     /// Generates an iterator of nodes with the specified range of values.
-    pub fn range_asc<S: AvltrieeSearch<T, I, A>>(
+    pub fn range_asc<S: AvltrieeSearch<T, I, A, R>>(
         s: &'a S,
         start: &I,
         end: &I,
-    ) -> AvltrieeIter<'a, T, I, A> {
+    ) -> AvltrieeIter<'a, T, I, A, R> {
         Self::range_inner(s, start, end, Order::Asc)
     }
 
     /// Generates an iterator of nodes with the specified range of values. Iterates in descending order.
-    pub fn range_desc<S: AvltrieeSearch<T, I, A>>(
+    pub fn range_desc<S: AvltrieeSearch<T, I, A, R>>(
         s: &'a S,
         start: &I,
         end: &I,
-    ) -> AvltrieeIter<'a, T, I, A> {
+    ) -> AvltrieeIter<'a, T, I, A, R> {
         Self::range_inner(s, start, end, Order::Desc)
     }
 
-    fn range_inner<S: AvltrieeSearch<T, I, A>>(
+    fn range_inner<S: AvltrieeSearch<T, I, A, R>>(
         s: &'a S,
         start: &I,
         end: &I,
         order: Order,
-    ) -> AvltrieeIter<'a, T, I, A> {
+    ) -> AvltrieeIter<'a, T, I, A, R> {
         let triee = s.as_ref();
         if let Some(range) = s.range(start, end) {
             AvltrieeIter::new(triee, Some(range.start), Some(range.end), order)
@@ -185,13 +211,15 @@ impl<'a, T, I: ?Sized, A: AvltrieeAllocator<T>> AvltrieeIter<'a, T, I, A> {
     }
 }
 
-impl<'a, T, I: ?Sized, A: AvltrieeAllocator<T>> Iterator for AvltrieeIter<'a, T, I, A> {
-    type Item = NonZeroU32;
+impl<'a, T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> Iterator
+    for AvltrieeIter<'a, T, I, A, R>
+{
+    type Item = R;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.now.map(|c| {
+        self.now.inspect(|&c| {
             self.now = if Some(c) == self.end_row {
-                let same = unsafe { self.triee.node_unchecked(c) }.same;
+                let same = unsafe { self.triee.same_unchecked(c) };
                 if same.is_some() {
                     self.end_row = same;
                 }
@@ -203,16 +231,15 @@ impl<'a, T, I: ?Sized, A: AvltrieeAllocator<T>> Iterator for AvltrieeIter<'a, T,
                     i
                 })
             };
-            c
         })
     }
 }
 
-impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
+impl<T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> Avltriee<T, I, A, R> {
     /// Generate an iterator.
-    pub fn iter(&self) -> AvltrieeIter<T, I, A> {
+    pub fn iter(&self) -> AvltrieeIter<'_, T, I, A, R> {
         AvltrieeIter::new(
-            &self,
+            self,
             self.min(self.root()),
             self.max(self.root()),
             Order::Asc,
@@ -220,9 +247,9 @@ impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
     }
 
     /// Generate an iterator. Iterates in descending order.
-    pub fn desc_iter(&self) -> AvltrieeIter<T, I, A> {
+    pub fn desc_iter(&self) -> AvltrieeIter<'_, T, I, A, R> {
         AvltrieeIter::new(
-            &self,
+            self,
             self.min(self.root()),
             self.max(self.root()),
             Order::Desc,
@@ -230,100 +257,96 @@ impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
     }
 
     /// Generates an iterator of nodes with the same value as the specified value.
-    pub fn iter_by<'a>(&'a self, value: &I) -> AvltrieeIter<T, I, A>
+    pub fn iter_by(&self, value: &I) -> AvltrieeIter<'_, T, I, A, R>
     where
-        Self: AvltrieeSearch<T, I, A>,
+        Self: AvltrieeSearch<T, I, A, R>,
     {
         AvltrieeIter::by(self, value)
     }
 
     /// Generates an iterator with values ​​starting from the specified value.
-    pub fn iter_from<'a>(&'a self, value: &I) -> AvltrieeIter<T, I, A>
+    pub fn iter_from(&self, value: &I) -> AvltrieeIter<'_, T, I, A, R>
     where
-        Self: AvltrieeSearch<T, I, A>,
+        Self: AvltrieeSearch<T, I, A, R>,
     {
         AvltrieeIter::from_asc(self, value)
     }
 
     /// Generates an iterator with values ​​starting from the specified value. Iterates in descending order.
-    pub fn desc_iter_from<'a>(&'a self, value: &I) -> AvltrieeIter<T, I, A>
+    pub fn desc_iter_from(&self, value: &I) -> AvltrieeIter<'_, T, I, A, R>
     where
-        Self: AvltrieeSearch<T, I, A>,
+        Self: AvltrieeSearch<T, I, A, R>,
     {
         AvltrieeIter::from_desc(self, value)
     }
 
     /// Generates an iterator of nodes with values ​​less than or equal to the specified value.
-    pub fn iter_to<'a>(&'a self, value: &I) -> AvltrieeIter<T, I, A>
+    pub fn iter_to(&self, value: &I) -> AvltrieeIter<'_, T, I, A, R>
     where
-        Self: AvltrieeSearch<T, I, A>,
+        Self: AvltrieeSearch<T, I, A, R>,
     {
         AvltrieeIter::to_asc(self, value)
     }
 
     /// Generates an iterator of nodes with values ​​less than or equal to the specified value. Iterates in descending order.
-    pub fn desc_iter_to<'a>(&'a self, value: &I) -> AvltrieeIter<T, I, A>
+    pub fn desc_iter_to(&self, value: &I) -> AvltrieeIter<'_, T, I, A, R>
     where
-        Self: AvltrieeSearch<T, I, A>,
+        Self: AvltrieeSearch<T, I, A, R>,
     {
         AvltrieeIter::to_desc(self, value)
     }
 
     /// Generates an iterator of nodes with values ​​greater than the specified value.
-    pub fn iter_over<'a>(&'a self, value: &I) -> AvltrieeIter<T, I, A>
+    pub fn iter_over(&self, value: &I) -> AvltrieeIter<'_, T, I, A, R>
     where
-        Self: AvltrieeSearch<T, I, A>,
+        Self: AvltrieeSearch<T, I, A, R>,
     {
         AvltrieeIter::over_asc(self, value)
     }
 
     /// Generates an iterator of nodes with values ​​greater than the specified value. Iterates in descending order.
-    pub fn desc_iter_over<'a>(&'a self, value: &I) -> AvltrieeIter<T, I, A>
+    pub fn desc_iter_over(&self, value: &I) -> AvltrieeIter<'_, T, I, A, R>
     where
-        Self: AvltrieeSearch<T, I, A>,
+        Self: AvltrieeSearch<T, I, A, R>,
     {
         AvltrieeIter::over_desc(self, value)
     }
 
     /// Generates an iterator of nodes with values ​​less than the specified value.
-    pub fn iter_under<'a>(&'a self, value: &I) -> AvltrieeIter<T, I, A>
+    pub fn iter_under(&self, value: &I) -> AvltrieeIter<'_, T, I, A, R>
     where
-        Self: AvltrieeSearch<T, I, A>,
+        Self: AvltrieeSearch<T, I, A, R>,
     {
         AvltrieeIter::under_asc(self, value)
     }
 
     /// Generates an iterator of nodes with values ​​less than the specified value. Iterates in descending order.
-    pub fn desc_iter_under<'a>(&'a self, value: &I) -> AvltrieeIter<T, I, A>
+    pub fn desc_iter_under(&self, value: &I) -> AvltrieeIter<'_, T, I, A, R>
     where
-        Self: AvltrieeSearch<T, I, A>,
+        Self: AvltrieeSearch<T, I, A, R>,
     {
         AvltrieeIter::under_desc(self, value)
     }
 
     /// Generates an iterator of nodes with the specified range of values.
-    pub fn iter_range<'a>(&'a self, start: &I, end: &I) -> AvltrieeIter<T, I, A>
+    pub fn iter_range(&self, start: &I, end: &I) -> AvltrieeIter<'_, T, I, A, R>
     where
-        Self: AvltrieeSearch<T, I, A>,
+        Self: AvltrieeSearch<T, I, A, R>,
     {
         AvltrieeIter::range_asc(self, start, end)
     }
 
     /// Generates an iterator of nodes with the specified range of values. Iterates in descending order.
-    pub fn desc_iter_range<'a>(&'a self, start: &I, end: &I) -> AvltrieeIter<T, I, A>
+    pub fn desc_iter_range(&self, start: &I, end: &I) -> AvltrieeIter<'_, T, I, A, R>
     where
-        Self: AvltrieeSearch<T, I, A>,
+        Self: AvltrieeSearch<T, I, A, R>,
     {
         AvltrieeIter::range_desc(self, start, end)
     }
 
-    fn next(
-        &self,
-        c: NonZeroU32,
-        same_branch: Option<NonZeroU32>,
-    ) -> Option<(NonZeroU32, Option<NonZeroU32>)> {
+    fn next(&self, c: R, same_branch: Option<R>) -> Option<(R, Option<R>)> {
         let mut node = unsafe { self.node_unchecked(c) };
-        if let Some(same) = node.same {
+        if let Some(same) = unsafe { self.same_unchecked(c) } {
             Some((
                 same,
                 if same_branch.is_some() {
@@ -353,7 +376,7 @@ impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
         }
     }
 
-    fn retroactive(&self, c: NonZeroU32) -> Option<NonZeroU32> {
+    fn retroactive(&self, c: R) -> Option<R> {
         unsafe { self.node_unchecked(c) }.parent.and_then(|parent| {
             if unsafe { self.node_unchecked(parent) }.right == Some(c) {
                 self.retroactive(parent).filter(|p| p.get() != c.get())
@@ -363,13 +386,9 @@ impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
         })
     }
 
-    fn next_desc(
-        &self,
-        c: NonZeroU32,
-        same_branch: Option<NonZeroU32>,
-    ) -> Option<(NonZeroU32, Option<NonZeroU32>)> {
+    fn next_desc(&self, c: R, same_branch: Option<R>) -> Option<(R, Option<R>)> {
         let mut node = unsafe { self.node_unchecked(c) };
-        if let Some(same) = node.same {
+        if let Some(same) = unsafe { self.same_unchecked(c) } {
             Some((
                 same,
                 Some(if let Some(same_branch) = same_branch {
@@ -398,7 +417,7 @@ impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
         }
     }
 
-    fn retroactive_desc(&self, c: NonZeroU32) -> Option<NonZeroU32> {
+    fn retroactive_desc(&self, c: R) -> Option<R> {
         unsafe { self.node_unchecked(c) }.parent.and_then(|parent| {
             if unsafe { self.node_unchecked(parent) }.left == Some(c) {
                 self.retroactive_desc(parent).filter(|p| *p != c)
@@ -0,0 +1,152 @@
+use std::num::NonZeroU32;
+
+use crate::{search::AvltrieeSearch, Avltriee, AvltrieeAllocator, AvltrieeRow};
+
+use super::Order;
+
+type DistinctNextFunc<T, I, A, R> = fn(&Avltriee<T, I, A, R>, R) -> Option<R>;
+
+/// Iterator that yields one entry per distinct value.
+/// Each item is the head row of the value and the number of rows having that value.
+pub struct AvltrieeDistinctIter<'a, T, I: ?Sized, A, R = NonZeroU32> {
+    now: Option<R>,
+    end_row: Option<R>,
+    triee: &'a Avltriee<T, I, A, R>,
+    next_func: DistinctNextFunc<T, I, A, R>,
+}
+
+impl<'a, T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow>
+    AvltrieeDistinctIter<'a, T, I, A, R>
+{
+    fn new(
+        triee: &'a Avltriee<T, I, A, R>,
+        now: Option<R>,
+        end_row: Option<R>,
+        order: Order,
+    ) -> AvltrieeDistinctIter<'a, T, I, A, R> {
+        match order {
+            Order::Asc => AvltrieeDistinctIter {
+                now,
+                end_row,
+                triee,
+                next_func: Avltriee::<T, I, A, R>::next_distinct,
+            },
+            Order::Desc => AvltrieeDistinctIter {
+                now: end_row,
+                end_row: now,
+                triee,
+                next_func: Avltriee::<T, I, A, R>::next_distinct_desc,
+            },
+        }
+    }
+
+    fn range_inner<S: AvltrieeSearch<T, I, A, R>>(
+        s: &'a S,
+        start: &I,
+        end: &I,
+        order: Order,
+    ) -> AvltrieeDistinctIter<'a, T, I, A, R> {
+        let triee = s.as_ref();
+        if let Some(range) = s.range(start, end) {
+            AvltrieeDistinctIter::new(triee, Some(range.start), Some(range.end), order)
+        } else {
+            AvltrieeDistinctIter::new(triee, None, None, order)
+        }
+    }
+}
+
+impl<'a, T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> Iterator
+    for AvltrieeDistinctIter<'a, T, I, A, R>
+{
+    type Item = (R, R::Count);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.now.map(|c| {
+            self.now = if Some(c) == self.end_row {
+                None
+            } else {
+                (self.next_func)(self.triee, c)
+            };
+            (c, self.triee.same_count(c))
+        })
+    }
+}
+
+impl<T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> Avltriee<T, I, A, R> {
+    /// Generates an iterator of distinct values.
+    /// Yields the head row of each value and the number of rows having that value.
+    pub fn iter_distinct(&self) -> AvltrieeDistinctIter<'_, T, I, A, R> {
+        AvltrieeDistinctIter::new(
+            self,
+            self.min(self.root()),
+            self.max(self.root()),
+            Order::Asc,
+        )
+    }
+
+    /// Generates an iterator of distinct values. Iterates in descending order.
+    pub fn desc_iter_distinct(&self) -> AvltrieeDistinctIter<'_, T, I, A, R> {
+        AvltrieeDistinctIter::new(
+            self,
+            self.min(self.root()),
+            self.max(self.root()),
+            Order::Desc,
+        )
+    }
+
+    /// Generates an iterator of distinct values in the specified range of values.
+    pub fn iter_distinct_range(&self, start: &I, end: &I) -> AvltrieeDistinctIter<'_, T, I, A, R>
+    where
+        Self: AvltrieeSearch<T, I, A, R>,
+    {
+        AvltrieeDistinctIter::range_inner(self, start, end, Order::Asc)
+    }
+
+    /// Generates an iterator of distinct values in the specified range of values. Iterates in descending order.
+    pub fn desc_iter_distinct_range(
+        &self,
+        start: &I,
+        end: &I,
+    ) -> AvltrieeDistinctIter<'_, T, I, A, R>
+    where
+        Self: AvltrieeSearch<T, I, A, R>,
+    {
+        AvltrieeDistinctIter::range_inner(self, start, end, Order::Desc)
+    }
+
+    /// Returns the number of rows that have the same value as the specified head row.
+    pub(crate) fn same_count(&self, row: R) -> R::Count {
+        let node = unsafe { self.node_unchecked(row) };
+        node.count() - self.count(node.left) - self.count(node.right)
+    }
+
+    fn next_distinct(&self, c: R) -> Option<R> {
+        let node = unsafe { self.node_unchecked(c) };
+        if node.right.is_some() {
+            self.min(node.right)
+        } else {
+            node.parent.and_then(|parent| {
+                if unsafe { self.node_unchecked(parent) }.left == Some(c) {
+                    Some(parent)
+                } else {
+                    self.retroactive(parent)
+                }
+            })
+        }
+    }
+
+    fn next_distinct_desc(&self, c: R) -> Option<R> {
+        let node = unsafe { self.node_unchecked(c) };
+        if node.left.is_some() {
+            self.max(node.left)
+        } else {
+            node.parent.and_then(|parent| {
+                if unsafe { self.node_unchecked(parent) }.right == Some(c) {
+                    Some(parent)
+                } else {
+                    self.retroactive_desc(parent)
+                }
+            })
+        }
+    }
+}
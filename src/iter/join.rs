@@ -0,0 +1,106 @@
+use std::{cmp::Ordering, num::NonZeroU32};
+
+use crate::{search::AvltrieeSearch, Avltriee, AvltrieeAllocator, AvltrieeRow};
+
+use super::{AvltrieeDistinctIter, AvltrieeIter, Order};
+
+/// Iterator that walks two triees in order and yields the same branches of each matching value.
+pub struct AvltrieeMergeJoin<'a, L, RS, TL, TR, I: ?Sized, AL, AR, R = NonZeroU32> {
+    left: &'a L,
+    right: &'a RS,
+    left_iter: AvltrieeDistinctIter<'a, TL, I, AL, R>,
+    right_iter: AvltrieeDistinctIter<'a, TR, I, AR, R>,
+    left_next: Option<R>,
+    right_next: Option<R>,
+}
+
+impl<'a, L, RS, TL, TR, I: ?Sized, AL, AR, R> AvltrieeMergeJoin<'a, L, RS, TL, TR, I, AL, AR, R>
+where
+    L: AvltrieeSearch<TL, I, AL, R>,
+    RS: AvltrieeSearch<TR, I, AR, R>,
+    AL: AvltrieeAllocator<TL, R>,
+    AR: AvltrieeAllocator<TR, R>,
+    R: AvltrieeRow,
+{
+    /// Generates an iterator that joins two triees on value.
+    pub fn new(left: &'a L, right: &'a RS) -> Self {
+        let mut left_iter = left.as_ref().iter_distinct();
+        let mut right_iter = right.as_ref().iter_distinct();
+        AvltrieeMergeJoin {
+            left,
+            right,
+            left_next: left_iter.next().map(|(row, _)| row),
+            right_next: right_iter.next().map(|(row, _)| row),
+            left_iter,
+            right_iter,
+        }
+    }
+}
+
+impl<'a, L, RS, TL, TR, I: ?Sized, AL, AR, R> Iterator
+    for AvltrieeMergeJoin<'a, L, RS, TL, TR, I, AL, AR, R>
+where
+    L: AvltrieeSearch<TL, I, AL, R>,
+    RS: AvltrieeSearch<TR, I, AR, R>,
+    AL: AvltrieeAllocator<TL, R>,
+    AR: AvltrieeAllocator<TR, R>,
+    R: AvltrieeRow,
+{
+    type Item = (
+        AvltrieeIter<'a, TL, I, AL, R>,
+        AvltrieeIter<'a, TR, I, AR, R>,
+    );
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let left_row = self.left_next?;
+            let right_row = self.right_next?;
+            match L::cmp(unsafe { self.left.value_unchecked(left_row) }, unsafe {
+                self.right.value_unchecked(right_row)
+            }) {
+                Ordering::Less => {
+                    self.left_next = self.left_iter.next().map(|(row, _)| row);
+                }
+                Ordering::Greater => {
+                    self.right_next = self.right_iter.next().map(|(row, _)| row);
+                }
+                Ordering::Equal => {
+                    self.left_next = self.left_iter.next().map(|(row, _)| row);
+                    self.right_next = self.right_iter.next().map(|(row, _)| row);
+                    return Some((
+                        AvltrieeIter::new(
+                            self.left.as_ref(),
+                            Some(left_row),
+                            Some(left_row),
+                            Order::Asc,
+                        ),
+                        AvltrieeIter::new(
+                            self.right.as_ref(),
+                            Some(right_row),
+                            Some(right_row),
+                            Order::Asc,
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+type TrieeMergeJoin<'a, T, T2, I, A, A2, R> =
+    AvltrieeMergeJoin<'a, Avltriee<T, I, A, R>, Avltriee<T2, I, A2, R>, T, T2, I, A, A2, R>;
+
+impl<T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> Avltriee<T, I, A, R> {
+    /// Generates an iterator that joins with another triee on value.
+    /// Yields iterators of the rows having each value that exists in both triees.
+    pub fn merge_join<'a, T2, A2: AvltrieeAllocator<T2, R>>(
+        &'a self,
+        other: &'a Avltriee<T2, I, A2, R>,
+    ) -> TrieeMergeJoin<'a, T, T2, I, A, A2, R>
+    where
+        Self: AvltrieeSearch<T, I, A, R>,
+        Avltriee<T2, I, A2, R>: AvltrieeSearch<T2, I, A2, R>,
+    {
+        AvltrieeMergeJoin::new(self, other)
+    }
+}
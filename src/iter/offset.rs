@@ -0,0 +1,191 @@
+use crate::{row::count_index, search::AvltrieeSearch, Avltriee, AvltrieeAllocator, AvltrieeRow};
+
+use super::{AvltrieeIter, Order};
+
+impl<'a, T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> AvltrieeIter<'a, T, I, A, R> {
+    fn with_offset(
+        triee: &'a Avltriee<T, I, A, R>,
+        start: Option<R>,
+        end: Option<R>,
+        offset: R::Count,
+        order: Order,
+    ) -> AvltrieeIter<'a, T, I, A, R> {
+        let (Some(start), Some(end)) = (start, end) else {
+            return AvltrieeIter::new(triee, None, None, order);
+        };
+        let (first, last) = match order {
+            Order::Asc => (start, end),
+            Order::Desc => (end, start),
+        };
+        let first_rank = triee.rank(first, &order);
+        let last_rank = triee.rank(last, &order) + triee.same_count(last) - R::Count::from(1);
+        if offset <= last_rank - first_rank {
+            let (row, head) = triee.select(first_rank + offset, &order);
+            let mut iter = AvltrieeIter::new(triee, None, None, order);
+            iter.now = Some(row);
+            iter.end_row = Some(if head == last { row } else { last });
+            if row != head {
+                iter.same_branch = Some(head);
+            }
+            iter
+        } else {
+            AvltrieeIter::new(triee, None, None, order)
+        }
+    }
+
+    /// Generates an iterator that starts at the specified offset.
+    pub fn from_offset_asc(
+        triee: &'a Avltriee<T, I, A, R>,
+        offset: R::Count,
+    ) -> AvltrieeIter<'a, T, I, A, R> {
+        Self::with_offset(
+            triee,
+            triee.min(triee.root()),
+            triee.max(triee.root()),
+            offset,
+            Order::Asc,
+        )
+    }
+
+    /// Generates an iterator that starts at the specified offset. Iterates in descending order.
+    pub fn from_offset_desc(
+        triee: &'a Avltriee<T, I, A, R>,
+        offset: R::Count,
+    ) -> AvltrieeIter<'a, T, I, A, R> {
+        Self::with_offset(
+            triee,
+            triee.min(triee.root()),
+            triee.max(triee.root()),
+            offset,
+            Order::Desc,
+        )
+    }
+
+    /// Generates an iterator of nodes with the specified range of values that starts at the specified offset.
+    pub fn range_from_offset_asc<S: AvltrieeSearch<T, I, A, R>>(
+        s: &'a S,
+        start: &I,
+        end: &I,
+        offset: R::Count,
+    ) -> AvltrieeIter<'a, T, I, A, R> {
+        Self::range_from_offset_inner(s, start, end, offset, Order::Asc)
+    }
+
+    /// Generates an iterator of nodes with the specified range of values that starts at the specified offset. Iterates in descending order.
+    pub fn range_from_offset_desc<S: AvltrieeSearch<T, I, A, R>>(
+        s: &'a S,
+        start: &I,
+        end: &I,
+        offset: R::Count,
+    ) -> AvltrieeIter<'a, T, I, A, R> {
+        Self::range_from_offset_inner(s, start, end, offset, Order::Desc)
+    }
+
+    fn range_from_offset_inner<S: AvltrieeSearch<T, I, A, R>>(
+        s: &'a S,
+        start: &I,
+        end: &I,
+        offset: R::Count,
+        order: Order,
+    ) -> AvltrieeIter<'a, T, I, A, R> {
+        let triee = s.as_ref();
+        let range = s.range(start, end);
+        Self::with_offset(
+            triee,
+            range.as_ref().map(|range| range.start),
+            range.as_ref().map(|range| range.end),
+            offset,
+            order,
+        )
+    }
+}
+
+impl<T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> Avltriee<T, I, A, R> {
+    /// Generates an iterator that skips the specified number of rows.
+    pub fn iter_from_offset(&self, offset: R::Count) -> AvltrieeIter<'_, T, I, A, R> {
+        AvltrieeIter::from_offset_asc(self, offset)
+    }
+
+    /// Generates an iterator that skips the specified number of rows. Iterates in descending order.
+    pub fn desc_iter_from_offset(&self, offset: R::Count) -> AvltrieeIter<'_, T, I, A, R> {
+        AvltrieeIter::from_offset_desc(self, offset)
+    }
+
+    /// Generates an iterator of nodes with the specified range of values that skips the specified number of rows.
+    pub fn iter_range_from_offset(
+        &self,
+        start: &I,
+        end: &I,
+        offset: R::Count,
+    ) -> AvltrieeIter<'_, T, I, A, R>
+    where
+        Self: AvltrieeSearch<T, I, A, R>,
+    {
+        AvltrieeIter::range_from_offset_asc(self, start, end, offset)
+    }
+
+    /// Generates an iterator of nodes with the specified range of values that skips the specified number of rows. Iterates in descending order.
+    pub fn desc_iter_range_from_offset(
+        &self,
+        start: &I,
+        end: &I,
+        offset: R::Count,
+    ) -> AvltrieeIter<'_, T, I, A, R>
+    where
+        Self: AvltrieeSearch<T, I, A, R>,
+    {
+        AvltrieeIter::range_from_offset_desc(self, start, end, offset)
+    }
+
+    /// Returns the number of rows preceding the specified head row in the specified order.
+    pub(crate) fn rank(&self, head: R, order: &Order) -> R::Count {
+        let node = unsafe { self.node_unchecked(head) };
+        let mut rank = self.count(match order {
+            Order::Asc => node.left,
+            Order::Desc => node.right,
+        });
+        let mut row = head;
+        while let Some(parent) = unsafe { self.node_unchecked(row) }.parent {
+            let parent_node = unsafe { self.node_unchecked(parent) };
+            let from = match order {
+                Order::Asc => parent_node.right,
+                Order::Desc => parent_node.left,
+            };
+            if from == Some(row) {
+                rank += parent_node.count() - self.count(Some(row));
+            }
+            row = parent;
+        }
+        rank
+    }
+
+    /// Returns the row at the specified position and the head row of its same branch.
+    /// Descends in O(log n), then walks the same branch up to the position within it.
+    pub(crate) fn select(&self, position: R::Count, order: &Order) -> (R, R) {
+        let mut position = position;
+        let mut row = self.root().unwrap();
+        loop {
+            let node = unsafe { self.node_unchecked(row) };
+            let (before, after) = match order {
+                Order::Asc => (node.left, node.right),
+                Order::Desc => (node.right, node.left),
+            };
+            let before_count = self.count(before);
+            if position < before_count {
+                row = before.unwrap();
+                continue;
+            }
+            position -= before_count;
+            let same_count = node.count() - before_count - self.count(after);
+            if position < same_count {
+                let mut same = row;
+                for _ in 0..count_index(position) {
+                    same = unsafe { self.same_unchecked(same) }.unwrap();
+                }
+                return (same, row);
+            }
+            position -= same_count;
+            row = after.unwrap();
+        }
+    }
+}
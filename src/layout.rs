@@ -0,0 +1,154 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    num::{NonZeroU16, NonZeroU32, NonZeroU64},
+};
+
+use crate::AvltrieeRow;
+
+/// How the height, the subtree count and the same link of a node are stored.
+pub trait AvltrieeLayout<R: AvltrieeRow> {
+    type Meta: Copy + Debug;
+
+    /// Same links kept outside of the nodes. () if the nodes hold them.
+    type SameTable: Default;
+
+    /// Maximum number of rows the layout can count.
+    const MAX_ROWS: usize;
+
+    fn meta(height: u8, count: R::Count) -> Self::Meta;
+    fn height(meta: &Self::Meta) -> u8;
+    fn count(meta: &Self::Meta) -> R::Count;
+    fn set_height(meta: &mut Self::Meta, height: u8);
+    fn set_count(meta: &mut Self::Meta, count: R::Count);
+
+    /// Returns the next row of the same branch. row is the row that owns meta.
+    fn same(meta: &Self::Meta, table: &Self::SameTable, row: R) -> Option<R>;
+    fn set_same(meta: &mut Self::Meta, table: &mut Self::SameTable, row: R, same: Option<R>);
+
+    /// Returns the bytes reserved by the table.
+    fn same_table_bytes(table: &Self::SameTable) -> usize;
+}
+
+/// Layout that stores the height, the subtree count and the same link in separate fields.
+#[derive(Clone, Copy, Debug)]
+pub struct WideAvltrieeLayout;
+
+/// Laid out in declaration order, like the node.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct WideMeta<R: AvltrieeRow> {
+    height: u8,
+    count: R::Count,
+    same: Option<R>,
+}
+
+impl<R: AvltrieeRow> AvltrieeLayout<R> for WideAvltrieeLayout {
+    type Meta = WideMeta<R>;
+    type SameTable = ();
+
+    const MAX_ROWS: usize = usize::MAX;
+
+    fn meta(height: u8, count: R::Count) -> Self::Meta {
+        WideMeta {
+            height,
+            count,
+            same: None,
+        }
+    }
+
+    fn height(meta: &Self::Meta) -> u8 {
+        meta.height
+    }
+
+    fn count(meta: &Self::Meta) -> R::Count {
+        meta.count
+    }
+
+    fn set_height(meta: &mut Self::Meta, height: u8) {
+        meta.height = height;
+    }
+
+    fn set_count(meta: &mut Self::Meta, count: R::Count) {
+        meta.count = count;
+    }
+
+    fn same(meta: &Self::Meta, _table: &Self::SameTable, _row: R) -> Option<R> {
+        meta.same
+    }
+
+    fn set_same(meta: &mut Self::Meta, _table: &mut Self::SameTable, _row: R, same: Option<R>) {
+        meta.same = same;
+    }
+
+    fn same_table_bytes(_table: &Self::SameTable) -> usize {
+        0
+    }
+}
+
+/// Layout that packs the height and a flag for the same link into the upper bits of the subtree count.
+/// The same links are kept in a table outside of the nodes, so nodes without duplicates need no space for them.
+/// Saves a link and the padding after the height at the cost of a lower row limit
+/// and a table lookup per step along a same branch.
+/// (NonZeroU16: 2047 rows, NonZeroU32: 33554431 rows, NonZeroU64: 2^56-1 rows)
+#[derive(Clone, Copy, Debug)]
+pub struct PackedAvltrieeLayout;
+
+macro_rules! impl_packed_layout {
+    ($($row:ty => $count:ty, $height_bits:expr),*) => {
+        $(
+            impl AvltrieeLayout<$row> for PackedAvltrieeLayout {
+                type Meta = $count;
+                type SameTable = HashMap<$row, $row>;
+
+                const MAX_ROWS: usize = (<$count>::MAX >> ($height_bits + 1)) as usize;
+
+                fn meta(height: u8, count: $count) -> Self::Meta {
+                    (height as $count) << (<$count>::BITS - $height_bits) | count
+                }
+
+                fn height(meta: &Self::Meta) -> u8 {
+                    (meta >> (<$count>::BITS - $height_bits)) as u8
+                }
+
+                fn count(meta: &Self::Meta) -> $count {
+                    meta & (<$count>::MAX >> ($height_bits + 1))
+                }
+
+                fn set_height(meta: &mut Self::Meta, height: u8) {
+                    *meta = (height as $count) << (<$count>::BITS - $height_bits)
+                        | *meta & (<$count>::MAX >> $height_bits);
+                }
+
+                fn set_count(meta: &mut Self::Meta, count: $count) {
+                    *meta = *meta & !(<$count>::MAX >> ($height_bits + 1)) | count;
+                }
+
+                fn same(meta: &Self::Meta, table: &Self::SameTable, row: $row) -> Option<$row> {
+                    (meta & (1 << (<$count>::BITS - $height_bits - 1)) != 0)
+                        .then(|| table[&row])
+                }
+
+                fn set_same(
+                    meta: &mut Self::Meta,
+                    table: &mut Self::SameTable,
+                    row: $row,
+                    same: Option<$row>,
+                ) {
+                    if let Some(same) = same {
+                        *meta |= (1 << (<$count>::BITS - $height_bits - 1));
+                        table.insert(row, same);
+                    } else {
+                        *meta &= !(1 << (<$count>::BITS - $height_bits - 1));
+                        table.remove(&row);
+                    }
+                }
+
+                fn same_table_bytes(table: &Self::SameTable) -> usize {
+                    table.capacity() * size_of::<($row, $row)>()
+                }
+            }
+        )*
+    };
+}
+impl_packed_layout!(NonZeroU16 => u16, 4, NonZeroU32 => u32, 6, NonZeroU64 => u64, 7);
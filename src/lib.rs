@@ -1,25 +1,51 @@
+pub mod rows;
 pub mod search;
 
 mod allocator;
 mod default;
+mod diff;
+mod dump;
+mod frozen;
 mod head;
+mod histogram;
 mod iter;
+mod layout;
 mod node;
+mod row;
+#[cfg(feature = "rand")]
+mod sample;
+mod stats;
 mod update;
+mod validate;
+mod wal;
 
 use std::{marker::PhantomData, num::NonZeroU32};
 
-use allocator::VecAvltrieeAllocator;
-
-pub use allocator::AvltrieeAllocator;
-pub use iter::AvltrieeIter;
+pub use allocator::{
+    AvltrieeAllocator, ChunkedAvltrieeAllocator, SplitAvltrieeAllocator, VecAvltrieeAllocator,
+};
+pub use diff::AvltrieeDiff;
+pub use frozen::{AvltrieeFrozen, AvltrieeFrozenIter};
+pub use histogram::AvltrieeHistogramBucket;
+pub use iter::{AvltrieeDistinctIter, AvltrieeIter, AvltrieeMergeJoin};
+pub use layout::{AvltrieeLayout, PackedAvltrieeLayout, WideAvltrieeLayout};
 pub use node::AvltrieeNode;
-pub use search::AvltrieeSearch;
+pub use row::AvltrieeRow;
+pub use search::{AvltrieeNearestIter, AvltrieeSearch};
+pub use stats::AvltrieeStats;
 pub use update::AvltrieeUpdate;
+pub use validate::AvltrieeValidationError;
+pub use wal::{AvltrieeWal, AvltrieeWalSync, AvltrieeWalValue};
+
+type AllocatorNode<T, R, A> = AvltrieeNode<
+    <A as AvltrieeAllocator<T, R>>::NodeValue,
+    R,
+    <A as AvltrieeAllocator<T, R>>::Layout,
+>;
 
-pub struct Avltriee<T, I: ?Sized = T, A = VecAvltrieeAllocator<T>> {
+pub struct Avltriee<T, I: ?Sized = T, A = VecAvltrieeAllocator<T>, R = NonZeroU32> {
     allocator: A,
-    _marker: PhantomData<fn(I, T)>,
+    _marker: PhantomData<fn(I, T, R)>,
 }
 
 impl<T: Default> Avltriee<T, T, VecAvltrieeAllocator<T>> {
@@ -32,7 +58,13 @@ impl<T: Default> Avltriee<T, T, VecAvltrieeAllocator<T>> {
     }
 }
 
-impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
+impl<T: Default> Default for Avltriee<T, T, VecAvltrieeAllocator<T>> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> Avltriee<T, I, A, R> {
     /// Creates the Avltriee with [AvltrieeAllocator].
     pub fn with_allocator(allocator: A) -> Self {
         Self {
@@ -42,39 +74,72 @@ impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
     }
 
     /// Returns the node of the specified row.
-    pub fn node(&self, row: NonZeroU32) -> Option<&AvltrieeNode<T>> {
+    pub fn node(&self, row: R) -> Option<&AllocatorNode<T, R, A>> {
         self.allocator
             .get(row)
-            .and_then(|node| (node.height != 0).then(|| unsafe { self.node_unchecked(row) }))
+            .and_then(|node| (node.height() != 0).then(|| unsafe { self.node_unchecked(row) }))
+    }
+
+    /// Returns the node of the specified row without checking.
+    /// # Safety
+    /// row must be within the allocated range.
+    pub unsafe fn node_unchecked(&self, row: R) -> &AllocatorNode<T, R, A> {
+        &*self.allocator.node_ptr(row.index())
     }
 
-    pub unsafe fn node_unchecked(&self, row: NonZeroU32) -> &AvltrieeNode<T> {
-        &*self.allocator.as_ptr().offset(row.get() as isize)
+    unsafe fn node_unchecked_mut(&mut self, row: R) -> &mut AllocatorNode<T, R, A> {
+        &mut *self.allocator.node_mut_ptr(row.index())
     }
 
-    unsafe fn node_unchecked_mut(&mut self, row: NonZeroU32) -> &mut AvltrieeNode<T> {
-        &mut *self.allocator.as_mut_ptr().offset(row.get() as isize)
+    /// Returns the next row of the same branch to which the specified row belongs.
+    /// # Safety
+    /// row must be within the allocated range.
+    pub(crate) unsafe fn same_unchecked(&self, row: R) -> Option<R> {
+        (*self.allocator.node_ptr(row.index())).same(self.allocator.same_table(), row)
+    }
+
+    unsafe fn set_same_unchecked(&mut self, row: R, same: Option<R>) {
+        let node = self.allocator.node_mut_ptr(row.index());
+        (*node).set_same(self.allocator.same_table_mut(), row, same);
+    }
+
+    /// Returns the value of the specified row without checking.
+    /// # Safety
+    /// row must be within the allocated range.
+    pub unsafe fn stored_value_unchecked(&self, row: R) -> &T {
+        &*self.allocator.value_ptr(row.index())
+    }
+
+    unsafe fn stored_value_unchecked_mut(&mut self, row: R) -> &mut T {
+        &mut *self.allocator.value_mut_ptr(row.index())
     }
 
     /// Checks whether the specified row is a node with a unique value.
-    pub fn is_unique(&self, row: NonZeroU32) -> Option<(bool, &AvltrieeNode<T>)> {
+    pub fn is_unique(&self, row: R) -> Option<(bool, &AllocatorNode<T, R, A>)> {
         self.node(row).map(|node| {
             (
-                node.same.is_none()
+                unsafe { self.same_unchecked(row) }.is_none()
                     && node.parent.is_some_and(|parent| {
-                        unsafe { self.node_unchecked(parent) }.same != Some(row)
+                        (unsafe { self.same_unchecked(parent) }) != Some(row)
                     }),
                 node,
             )
         })
     }
 
-    fn allocate(&mut self, rows: NonZeroU32) {
-        self.allocator.resize(rows.get());
-        self.set_rows_count(rows.get());
+    /// Grows the allocator to hold the specified row. Never shrinks, so higher rows are kept.
+    fn allocate(&mut self, rows: R) {
+        if rows.get() > self.rows_count() {
+            assert!(
+                rows.index() <= A::Layout::MAX_ROWS,
+                "row exceeds the limit of the layout"
+            );
+            self.allocator.resize(rows.index());
+            self.set_rows_count(rows.get());
+        }
     }
 
-    fn min(&self, t: Option<NonZeroU32>) -> Option<NonZeroU32> {
+    fn min(&self, t: Option<R>) -> Option<R> {
         let mut t = t;
         while let Some(t_inner) = t {
             let l = unsafe { self.node_unchecked(t_inner) }.left;
@@ -87,7 +152,7 @@ impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
         t
     }
 
-    fn max(&self, t: Option<NonZeroU32>) -> Option<NonZeroU32> {
+    fn max(&self, t: Option<R>) -> Option<R> {
         let mut t = t;
         while let Some(t_inner) = t {
             let r = unsafe { self.node_unchecked(t_inner) }.right;
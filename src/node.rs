@@ -1,28 +1,94 @@
-use std::num::NonZeroU32;
-
-#[derive(Clone, Debug, Default)]
-pub struct AvltrieeNode<T> {
-    pub(super) parent: Option<NonZeroU32>,
-    pub(super) left: Option<NonZeroU32>,
-    pub(super) right: Option<NonZeroU32>,
-    pub(super) same: Option<NonZeroU32>,
-    pub(super) height: u8,
-    value: T,
+use std::{
+    fmt::{self, Debug},
+    num::NonZeroU32,
+};
+
+use crate::{AvltrieeLayout, AvltrieeRow, WideAvltrieeLayout};
+
+#[repr(C)]
+pub struct AvltrieeNode<T, R: AvltrieeRow = NonZeroU32, L: AvltrieeLayout<R> = WideAvltrieeLayout> {
+    pub(super) parent: Option<R>,
+    pub(super) left: Option<R>,
+    pub(super) right: Option<R>,
+    meta: L::Meta,
+    pub(super) value: T,
 }
 
-impl<T> AvltrieeNode<T> {
-    pub(crate) fn new(parent: Option<NonZeroU32>, value: T) -> Self {
+impl<T: Clone, R: AvltrieeRow, L: AvltrieeLayout<R>> Clone for AvltrieeNode<T, R, L> {
+    fn clone(&self) -> Self {
         AvltrieeNode {
-            height: 1,
-            parent,
+            meta: self.meta,
+            parent: self.parent,
+            left: self.left,
+            right: self.right,
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl<T: Debug, R: AvltrieeRow, L: AvltrieeLayout<R>> Debug for AvltrieeNode<T, R, L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AvltrieeNode")
+            .field("parent", &self.parent)
+            .field("left", &self.left)
+            .field("right", &self.right)
+            .field("height", &self.height())
+            .field("count", &self.count())
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+impl<T: Default, R: AvltrieeRow, L: AvltrieeLayout<R>> Default for AvltrieeNode<T, R, L> {
+    fn default() -> Self {
+        AvltrieeNode {
+            meta: L::meta(0, R::Count::default()),
+            parent: None,
             left: None,
             right: None,
-            same: None,
-            value,
+            value: T::default(),
         }
     }
+}
+
+impl<T, R: AvltrieeRow, L: AvltrieeLayout<R>> AvltrieeNode<T, R, L> {
+    /// Resets the links for a new row with a unique value.
+    /// The same link must already be None.
+    pub(crate) fn init(&mut self, parent: Option<R>) {
+        self.meta = L::meta(1, R::Count::from(1));
+        self.parent = parent;
+        self.left = None;
+        self.right = None;
+    }
 
-    pub(crate) fn changeling(&mut self, current_child: NonZeroU32, new_child: Option<NonZeroU32>) {
+    pub(crate) fn height(&self) -> u8 {
+        L::height(&self.meta)
+    }
+
+    pub(crate) fn set_height(&mut self, height: u8) {
+        L::set_height(&mut self.meta, height);
+    }
+
+    /// Returns the number of rows in the subtree, including the same branch.
+    /// Only maintained for the head of a same branch; the other rows of the branch keep a stale count.
+    pub(crate) fn count(&self) -> R::Count {
+        L::count(&self.meta)
+    }
+
+    pub(crate) fn set_count(&mut self, count: R::Count) {
+        L::set_count(&mut self.meta, count);
+    }
+
+    /// Returns the next row of the same branch. row is the row of this node.
+    pub(crate) fn same(&self, table: &L::SameTable, row: R) -> Option<R> {
+        L::same(&self.meta, table, row)
+    }
+
+    pub(crate) fn set_same(&mut self, table: &mut L::SameTable, row: R, same: Option<R>) {
+        L::set_same(&mut self.meta, table, row, same);
+    }
+
+    pub(crate) fn changeling(&mut self, current_child: R, new_child: Option<R>) {
         if self.right == Some(current_child) {
             self.right = new_child;
         } else if self.left == Some(current_child) {
@@ -30,27 +96,21 @@ impl<T> AvltrieeNode<T> {
         }
     }
 
-    pub(crate) fn same_clone(&mut self, self_row: NonZeroU32, new_row: NonZeroU32) -> Self
-    where
-        T: Clone,
-    {
-        let cloned = AvltrieeNode {
-            height: self.height,
-            parent: self.parent,
-            left: self.left,
-            right: self.right,
-            same: Some(self_row),
-            value: self.value.clone(),
-        };
+    /// Moves the position of this node to new_node and hangs this node from it as the same branch.
+    /// The value is not copied, and the same link of new_node is left to the caller.
+    pub(crate) fn same_clone(&mut self, new_row: R, new_node: &mut Self) {
+        new_node.meta = self.meta;
+        new_node.parent = self.parent;
+        new_node.left = self.left;
+        new_node.right = self.right;
+
         self.left = None;
         self.right = None;
         self.parent = Some(new_row);
-
-        cloned
     }
 }
 
-impl<T> std::ops::Deref for AvltrieeNode<T> {
+impl<T, R: AvltrieeRow, L: AvltrieeLayout<R>> std::ops::Deref for AvltrieeNode<T, R, L> {
     type Target = T;
     fn deref(&self) -> &Self::Target {
         &self.value
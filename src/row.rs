@@ -0,0 +1,78 @@
+use std::{
+    fmt::{Debug, Display},
+    hash::Hash,
+    num::{NonZeroU16, NonZeroU32, NonZeroU64},
+    ops::{Add, AddAssign, Sub, SubAssign},
+};
+
+/// Row number type of the triee.
+pub trait AvltrieeRow: Copy + Ord + Hash + Debug + Display {
+    /// Unsigned integer type used for counts of rows.
+    type Count: Copy
+        + Ord
+        + Default
+        + Debug
+        + Display
+        + Hash
+        + Add<Output = Self::Count>
+        + Sub<Output = Self::Count>
+        + AddAssign
+        + SubAssign
+        + From<u8>
+        + TryFrom<usize>
+        + TryInto<usize>;
+
+    fn get(self) -> Self::Count;
+    fn new(count: Self::Count) -> Option<Self>;
+
+    /// # Safety
+    /// count must not be zero.
+    unsafe fn new_unchecked(count: Self::Count) -> Self;
+
+    /// Returns the row as an index of the allocator.
+    fn index(self) -> usize;
+
+    /// Returns the row of the specified index. Returns None if index is zero or out of range.
+    fn from_index(index: usize) -> Option<Self>;
+}
+
+/// Converts usize to a count of rows.
+pub(crate) fn index_count<C: TryFrom<usize>>(index: usize) -> C {
+    C::try_from(index).ok().expect("count of rows overflowed")
+}
+
+/// Converts a count of rows to usize.
+pub(crate) fn count_index<C: TryInto<usize>>(count: C) -> usize {
+    count.try_into().unwrap_or(usize::MAX)
+}
+
+macro_rules! impl_row {
+    ($($row:ty => $count:ty),*) => {
+        $(
+            impl AvltrieeRow for $row {
+                type Count = $count;
+
+                fn get(self) -> Self::Count {
+                    <$row>::get(self)
+                }
+
+                fn new(count: Self::Count) -> Option<Self> {
+                    <$row>::new(count)
+                }
+
+                unsafe fn new_unchecked(count: Self::Count) -> Self {
+                    <$row>::new_unchecked(count)
+                }
+
+                fn index(self) -> usize {
+                    usize::try_from(self.get()).expect("row exceeds the address space")
+                }
+
+                fn from_index(index: usize) -> Option<Self> {
+                    <$count>::try_from(index).ok().and_then(<$row>::new)
+                }
+            }
+        )*
+    };
+}
+impl_row!(NonZeroU16 => u16, NonZeroU32 => u32, NonZeroU64 => u64);
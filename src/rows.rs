@@ -0,0 +1,236 @@
+//! Combinators for row sets produced by iterators of multiple triees sharing the same rows.
+//!
+//! Triee iterators yield rows in value order, so each input is collected and sorted by row first.
+//! Dense inputs, whose highest row is at most 64 times their length, are sorted through a [RowBitmap] in linear time.
+//! Other inputs are sorted in O(n log n). The sorted inputs are then merged by galloping.
+
+use std::{marker::PhantomData, num::NonZeroU32};
+
+use crate::{row::count_index, AvltrieeRow};
+
+/// Collects the rows in ascending order without duplicates.
+fn sorted<R: AvltrieeRow, S: IntoIterator<Item = R>>(rows: S) -> Vec<R> {
+    let mut rows: Vec<R> = rows.into_iter().collect();
+    match rows.iter().max() {
+        Some(&max) if max.index() / 64 <= rows.len() => {
+            let mut bitmap = RowBitmap::with_rows_count(max.get());
+            bitmap.extend(rows.drain(..));
+            rows.extend(bitmap.iter());
+        }
+        _ => {
+            rows.sort_unstable();
+            rows.dedup();
+        }
+    }
+    rows
+}
+
+/// Returns the position of the first row greater than or equal to the specified row, searching from start by galloping.
+fn gallop<R: AvltrieeRow>(rows: &[R], start: usize, row: R) -> usize {
+    let mut step = 1;
+    let mut end = start;
+    while end < rows.len() && rows[end] < row {
+        end = start + step;
+        step *= 2;
+    }
+    let begin = start + step / 4;
+    let end = end.min(rows.len());
+    begin + rows[begin..end].partition_point(|&r| r < row)
+}
+
+/// Returns rows that appear in all of the specified row iterators, in ascending row order.
+pub fn intersection<I, S, R>(iters: I) -> Vec<R>
+where
+    I: IntoIterator<Item = S>,
+    S: IntoIterator<Item = R>,
+    R: AvltrieeRow,
+{
+    let mut sets: Vec<Vec<R>> = iters.into_iter().map(sorted).collect();
+    sets.sort_unstable_by_key(|set| set.len());
+    let mut sets = sets.into_iter();
+    let Some(mut result) = sets.next() else {
+        return vec![];
+    };
+    for set in sets {
+        let mut pos = 0;
+        result.retain(|&row| {
+            pos = gallop(&set, pos, row);
+            pos < set.len() && set[pos] == row
+        });
+        if result.is_empty() {
+            break;
+        }
+    }
+    result
+}
+
+/// Returns rows that appear in any of the specified row iterators, in ascending row order.
+pub fn union<I, S, R>(iters: I) -> Vec<R>
+where
+    I: IntoIterator<Item = S>,
+    S: IntoIterator<Item = R>,
+    R: AvltrieeRow,
+{
+    sorted(iters.into_iter().flatten())
+}
+
+/// Returns rows of the first iterator that do not appear in the second, in ascending row order.
+pub fn difference<L, S, R>(left: L, right: S) -> Vec<R>
+where
+    L: IntoIterator<Item = R>,
+    S: IntoIterator<Item = R>,
+    R: AvltrieeRow,
+{
+    let mut result = sorted(left);
+    let right = sorted(right);
+    let mut pos = 0;
+    result.retain(|&row| {
+        pos = gallop(&right, pos, row);
+        pos >= right.len() || right[pos] != row
+    });
+    result
+}
+
+/// Dense bitset of rows.
+/// Can be collected from any row iterator, e.g. `triee.iter_range(&start, &end).collect::<RowBitmap>()`.
+#[derive(Clone, Debug)]
+pub struct RowBitmap<R = NonZeroU32> {
+    words: Vec<u64>,
+    _marker: PhantomData<R>,
+}
+
+impl<R: AvltrieeRow> Default for RowBitmap<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R: AvltrieeRow> RowBitmap<R> {
+    /// Creates an empty bitmap.
+    pub fn new() -> Self {
+        Self::with_words(vec![])
+    }
+
+    /// Creates an empty bitmap with space for rows up to rows_count.
+    pub fn with_rows_count(rows_count: R::Count) -> Self {
+        Self::with_words(Vec::with_capacity(count_index(rows_count) / 64 + 1))
+    }
+
+    fn with_words(words: Vec<u64>) -> Self {
+        Self {
+            words,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Adds the specified row.
+    pub fn insert(&mut self, row: R) {
+        let (word, bit) = Self::position(row);
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        self.words[word] |= bit;
+    }
+
+    /// Removes the specified row.
+    pub fn remove(&mut self, row: R) {
+        let (word, bit) = Self::position(row);
+        if let Some(word) = self.words.get_mut(word) {
+            *word &= !bit;
+        }
+    }
+
+    /// Checks whether the specified row is contained.
+    pub fn contains(&self, row: R) -> bool {
+        let (word, bit) = Self::position(row);
+        self.words.get(word).is_some_and(|word| word & bit != 0)
+    }
+
+    /// Returns count of rows.
+    pub fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns true if no rows are contained.
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|word| *word == 0)
+    }
+
+    /// Returns rows contained in both bitmaps.
+    pub fn and(&self, other: &RowBitmap<R>) -> RowBitmap<R> {
+        RowBitmap::with_words(
+            self.words
+                .iter()
+                .zip(other.words.iter())
+                .map(|(l, r)| l & r)
+                .collect(),
+        )
+    }
+
+    /// Returns rows contained in either bitmap.
+    pub fn or(&self, other: &RowBitmap<R>) -> RowBitmap<R> {
+        let (long, short) = if self.words.len() >= other.words.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let mut words = long.words.clone();
+        for (word, other) in words.iter_mut().zip(short.words.iter()) {
+            *word |= other;
+        }
+        RowBitmap::with_words(words)
+    }
+
+    /// Returns rows from 1 to rows_count that are not contained.
+    pub fn not(&self, rows_count: R::Count) -> RowBitmap<R> {
+        let rows_count = count_index(rows_count);
+        let len = rows_count / 64 + 1;
+        let mut words: Vec<u64> = (0..len)
+            .map(|i| !self.words.get(i).copied().unwrap_or(0))
+            .collect();
+        words[0] &= !1;
+        let tail = (rows_count + 1) % 64;
+        if tail != 0 {
+            words[len - 1] &= (1 << tail) - 1;
+        }
+        RowBitmap::with_words(words)
+    }
+
+    /// Generates an iterator of rows in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = R> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                (word != 0).then(|| {
+                    let bit = word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    R::from_index(i * 64 + bit).unwrap()
+                })
+            })
+        })
+    }
+
+    fn position(row: R) -> (usize, u64) {
+        let row = row.index();
+        (row / 64, 1 << (row % 64))
+    }
+}
+
+impl<R: AvltrieeRow> FromIterator<R> for RowBitmap<R> {
+    fn from_iter<S: IntoIterator<Item = R>>(iter: S) -> Self {
+        let mut bitmap = RowBitmap::new();
+        bitmap.extend(iter);
+        bitmap
+    }
+}
+
+impl<R: AvltrieeRow> Extend<R> for RowBitmap<R> {
+    fn extend<S: IntoIterator<Item = R>>(&mut self, iter: S) {
+        for row in iter {
+            self.insert(row);
+        }
+    }
+}
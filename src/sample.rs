@@ -0,0 +1,33 @@
+use rand::Rng;
+
+use crate::{
+    iter::Order,
+    row::{count_index, index_count},
+    Avltriee, AvltrieeAllocator, AvltrieeRow, AvltrieeSearch,
+};
+
+impl<T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> Avltriee<T, I, A, R> {
+    /// Returns a uniformly random row. Each row of the same value is equally likely.
+    /// Returns None if the triee is empty.
+    pub fn sample<G: Rng + ?Sized>(&self, rng: &mut G) -> Option<R> {
+        let len = count_index(self.len());
+        (len > 0).then(|| {
+            self.select(index_count(rng.gen_range(0..len)), &Order::Asc)
+                .0
+        })
+    }
+
+    /// Returns a uniformly random row with the specified range of values.
+    /// Returns None if no row is in the range.
+    pub fn sample_range<G: Rng + ?Sized>(&self, start: &I, end: &I, rng: &mut G) -> Option<R>
+    where
+        Self: AvltrieeSearch<T, I, A, R>,
+    {
+        self.range(start, end).map(|range| {
+            let first = count_index(self.rank(range.start, &Order::Asc));
+            let last = count_index(self.rank(range.end, &Order::Asc) + self.same_count(range.end));
+            self.select(index_count(rng.gen_range(first..last)), &Order::Asc)
+                .0
+        })
+    }
+}
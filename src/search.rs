@@ -1,25 +1,47 @@
-use std::{cmp::Ordering, num::NonZeroU32, ops::Range};
+mod nearest;
 
-use crate::{Avltriee, AvltrieeAllocator, AvltrieeNode};
+use std::{cmp::Ordering, iter::Take, num::NonZeroU32, ops::Range};
 
-pub(crate) type Edge = (Option<NonZeroU32>, Ordering);
+use crate::{
+    validate::AvltrieeValidationError, Avltriee, AvltrieeAllocator, AvltrieeNode, AvltrieeRow,
+};
 
-pub trait AvltrieeSearch<T, I: ?Sized, A: AvltrieeAllocator<T>>: AsRef<Avltriee<T, I, A>> {
+pub use nearest::AvltrieeNearestIter;
+
+pub(crate) type Edge<R> = (Option<R>, Ordering);
+
+pub trait AvltrieeSearch<T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow = NonZeroU32>:
+    AsRef<Avltriee<T, I, A, R>>
+{
     fn cmp(left: &I, right: &I) -> Ordering;
-    fn value(&self, row: NonZeroU32) -> Option<&I>;
-    unsafe fn value_unchecked(&self, row: NonZeroU32) -> &I;
-    unsafe fn node_value_unchecked(&self, row: NonZeroU32) -> (&AvltrieeNode<T>, &I);
+    fn value(&self, row: R) -> Option<&I>;
+
+    /// # Safety
+    /// row must be a live row.
+    unsafe fn value_unchecked(&self, row: R) -> &I;
+
+    /// # Safety
+    /// row must be a live row.
+    unsafe fn node_value_unchecked(
+        &self,
+        row: R,
+    ) -> (&AvltrieeNode<A::NodeValue, R, A::Layout>, &I);
+
+    /// Checks links, heights, counts and value order of all nodes.
+    fn validate(&self) -> Result<(), AvltrieeValidationError<R>> {
+        crate::validate::validate(self)
+    }
 
     /// Search row of a value.
-    fn row(&self, value: &I) -> Option<NonZeroU32> {
+    fn row(&self, value: &I) -> Option<R> {
         let edge = self.edge(value);
-        (edge.1 == Ordering::Equal).then(|| edge.0).flatten()
+        (edge.1 == Ordering::Equal).then_some(edge.0).flatten()
     }
 
     /// Finds the edge of a node from the specified value.
-    fn edge(&self, value: &I) -> Edge {
+    fn edge(&self, value: &I) -> Edge<R> {
         let triee = self.as_ref();
-        let mut row: Option<NonZeroU32> = triee.root();
+        let mut row: Option<R> = triee.root();
         let mut ord = Ordering::Equal;
         while let Some(row_inner) = row {
             let (node, node_value) = unsafe { self.node_value_unchecked(row_inner) };
@@ -48,7 +70,7 @@ pub trait AvltrieeSearch<T, I: ?Sized, A: AvltrieeAllocator<T>>: AsRef<Avltriee<
     }
 
     /// Search >= value.
-    fn ge(&self, value: &I) -> Option<NonZeroU32> {
+    fn ge(&self, value: &I) -> Option<R> {
         let triee = self.as_ref();
         let mut row = triee.root();
         let mut keep = None;
@@ -79,7 +101,7 @@ pub trait AvltrieeSearch<T, I: ?Sized, A: AvltrieeAllocator<T>>: AsRef<Avltriee<
     }
 
     /// Search <= value.
-    fn le(&self, value: &I) -> Option<NonZeroU32> {
+    fn le(&self, value: &I) -> Option<R> {
         let triee = self.as_ref();
         let mut row = triee.root();
         let mut keep = None;
@@ -110,7 +132,7 @@ pub trait AvltrieeSearch<T, I: ?Sized, A: AvltrieeAllocator<T>>: AsRef<Avltriee<
     }
 
     /// Search > value.
-    fn gt(&self, value: &I) -> Option<NonZeroU32> {
+    fn gt(&self, value: &I) -> Option<R> {
         let triee = self.as_ref();
         let mut row = triee.root();
         let mut keep = None;
@@ -149,7 +171,7 @@ pub trait AvltrieeSearch<T, I: ?Sized, A: AvltrieeAllocator<T>>: AsRef<Avltriee<
     }
 
     /// Search < value.
-    fn lt(&self, value: &I) -> Option<NonZeroU32> {
+    fn lt(&self, value: &I) -> Option<R> {
         let triee = self.as_ref();
         let mut row = triee.root();
         let mut keep = None;
@@ -188,7 +210,7 @@ pub trait AvltrieeSearch<T, I: ?Sized, A: AvltrieeAllocator<T>>: AsRef<Avltriee<
     }
 
     /// Search with range value with custom ord.
-    fn range(&self, start_value: &I, end_value: &I) -> Option<Range<NonZeroU32>> {
+    fn range(&self, start_value: &I, end_value: &I) -> Option<Range<R>> {
         let triee = self.as_ref();
         let mut row = triee.root();
         let mut start = None;
@@ -251,4 +273,44 @@ pub trait AvltrieeSearch<T, I: ?Sized, A: AvltrieeAllocator<T>>: AsRef<Avltriee<
         }
         None
     }
+
+    /// Generates an iterator of rows in order of distance from the specified value.
+    /// Expands outward from the specified value in both directions. Ties prefer the smaller value.
+    fn nearest_iter<'a, F, D>(
+        &'a self,
+        value: &'a I,
+        distance: F,
+    ) -> AvltrieeNearestIter<'a, Self, T, I, A, R, F, D>
+    where
+        Self: Sized,
+        F: Fn(&I, &I) -> D,
+        D: PartialOrd,
+    {
+        AvltrieeNearestIter::new(self, value, distance)
+    }
+
+    /// Search the row whose value is closest to the specified value.
+    fn nearest<F, D>(&self, value: &I, distance: F) -> Option<R>
+    where
+        Self: Sized,
+        F: Fn(&I, &I) -> D,
+        D: PartialOrd,
+    {
+        self.nearest_iter(value, distance).next()
+    }
+
+    /// Generates an iterator of the k rows closest to the specified value.
+    fn k_nearest<'a, F, D>(
+        &'a self,
+        value: &'a I,
+        k: usize,
+        distance: F,
+    ) -> Take<AvltrieeNearestIter<'a, Self, T, I, A, R, F, D>>
+    where
+        Self: Sized,
+        F: Fn(&I, &I) -> D,
+        D: PartialOrd,
+    {
+        self.nearest_iter(value, distance).take(k)
+    }
 }
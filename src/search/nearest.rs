@@ -0,0 +1,68 @@
+use crate::{AvltrieeAllocator, AvltrieeIter, AvltrieeRow, AvltrieeSearch};
+
+/// Iterator that yields rows in order of distance from the specified value.
+/// Rows with the same value are yielded in succession.
+pub struct AvltrieeNearestIter<'a, S, T, I: ?Sized, A, R, F, D> {
+    s: &'a S,
+    value: &'a I,
+    distance: F,
+    lower: AvltrieeIter<'a, T, I, A, R>,
+    upper: AvltrieeIter<'a, T, I, A, R>,
+    lower_next: Option<(R, D)>,
+    upper_next: Option<(R, D)>,
+}
+
+impl<'a, S, T, I: ?Sized, A, R, F, D> AvltrieeNearestIter<'a, S, T, I, A, R, F, D>
+where
+    S: AvltrieeSearch<T, I, A, R>,
+    A: AvltrieeAllocator<T, R>,
+    R: AvltrieeRow,
+    F: Fn(&I, &I) -> D,
+{
+    pub(crate) fn new(s: &'a S, value: &'a I, distance: F) -> Self {
+        let mut iter = AvltrieeNearestIter {
+            s,
+            value,
+            distance,
+            lower: AvltrieeIter::to_desc(s, value),
+            upper: AvltrieeIter::over_asc(s, value),
+            lower_next: None,
+            upper_next: None,
+        };
+        iter.lower_next = iter.lower.next().map(|row| (row, iter.distance_of(row)));
+        iter.upper_next = iter.upper.next().map(|row| (row, iter.distance_of(row)));
+        iter
+    }
+
+    fn distance_of(&self, row: R) -> D {
+        (self.distance)(self.value, unsafe { self.s.value_unchecked(row) })
+    }
+}
+
+impl<'a, S, T, I: ?Sized, A, R, F, D: PartialOrd> Iterator
+    for AvltrieeNearestIter<'a, S, T, I, A, R, F, D>
+where
+    S: AvltrieeSearch<T, I, A, R>,
+    A: AvltrieeAllocator<T, R>,
+    R: AvltrieeRow,
+    F: Fn(&I, &I) -> D,
+{
+    type Item = R;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let take_lower = match (&self.lower_next, &self.upper_next) {
+            (Some(lower), Some(upper)) => lower.1 <= upper.1,
+            (Some(_), None) => true,
+            (None, Some(_)) => false,
+            (None, None) => return None,
+        };
+        if take_lower {
+            let next = self.lower.next().map(|row| (row, self.distance_of(row)));
+            std::mem::replace(&mut self.lower_next, next)
+        } else {
+            let next = self.upper.next().map(|row| (row, self.distance_of(row)));
+            std::mem::replace(&mut self.upper_next, next)
+        }
+        .map(|(row, _)| row)
+    }
+}
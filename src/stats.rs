@@ -0,0 +1,76 @@
+use crate::{row::count_index, Avltriee, AvltrieeAllocator, AvltrieeRow};
+
+/// Shape and memory usage of a triee. Returned by [Avltriee::stats].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AvltrieeStats {
+    /// Number of rows holding a value.
+    pub rows: usize,
+    /// Number of row slots allocated. This is [Avltriee::rows_count].
+    pub slots: usize,
+    /// Number of allocated slots without a value.
+    pub holes: usize,
+    /// Number of distinct values.
+    pub distinct: usize,
+    /// Number of rows of the most duplicated value.
+    pub max_same: usize,
+    /// Average number of rows per distinct value.
+    pub avg_same: f64,
+    /// Height of the triee, counting distinct values only.
+    pub height: u8,
+    /// Maximum height of an AVL tree with the same number of distinct values.
+    pub max_height: u8,
+    /// Bytes reserved by the allocator.
+    pub allocated_bytes: usize,
+}
+
+impl<T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> Avltriee<T, I, A, R> {
+    /// Collects statistics of the triee. Visits every row.
+    pub fn stats(&self) -> AvltrieeStats {
+        let mut stats = AvltrieeStats {
+            slots: count_index(self.rows_count()),
+            allocated_bytes: self.allocator.allocated_bytes(),
+            ..Default::default()
+        };
+
+        let mut stack: Vec<R> = self.root().into_iter().collect();
+        while let Some(row) = stack.pop() {
+            let node = unsafe { self.node_unchecked(row) };
+            stack.extend(node.left);
+            stack.extend(node.right);
+
+            let mut same_len = 1;
+            let mut same = unsafe { self.same_unchecked(row) };
+            while let Some(same_row) = same {
+                same_len += 1;
+                same = unsafe { self.same_unchecked(same_row) };
+            }
+            stats.distinct += 1;
+            stats.rows += same_len;
+            stats.max_same = stats.max_same.max(same_len);
+        }
+
+        stats.holes = stats.slots - stats.rows;
+        if stats.distinct > 0 {
+            stats.avg_same = stats.rows as f64 / stats.distinct as f64;
+        }
+        stats.height = self
+            .root()
+            .map_or(0, |root| unsafe { self.node_unchecked(root) }.height());
+        stats.max_height = avl_max_height(stats.distinct);
+        stats
+    }
+}
+
+/// Returns the maximum height of an AVL tree of n nodes.
+/// The sparsest tree of height h has N(h) = N(h-1) + N(h-2) + 1 nodes.
+fn avl_max_height(n: usize) -> u8 {
+    let (mut height, mut nodes, mut prev) = (0, 0usize, 0usize);
+    loop {
+        let next = if height == 0 { 1 } else { nodes + prev + 1 };
+        if next > n {
+            return height;
+        }
+        (prev, nodes) = (nodes, next);
+        height += 1;
+    }
+}
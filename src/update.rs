@@ -1,30 +1,39 @@
 mod balance;
 mod delete;
+mod rebuild;
+mod split;
 
 use std::{cmp::Ordering, num::NonZeroU32};
 
-use crate::{search::Edge, AvltrieeAllocator, AvltrieeSearch};
+use crate::{
+    row::count_index, search::Edge, AvltrieeAllocator, AvltrieeIter, AvltrieeRow, AvltrieeSearch,
+};
 
-use super::{Avltriee, AvltrieeNode};
+use super::Avltriee;
 
-pub trait AvltrieeUpdate<T, I: ?Sized, A: AvltrieeAllocator<T>>:
-    AsMut<Avltriee<T, I, A>> + AvltrieeSearch<T, I, A>
+pub trait AvltrieeUpdate<T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow = NonZeroU32>:
+    AsMut<Avltriee<T, I, A, R>> + AvltrieeSearch<T, I, A, R>
 {
     fn convert_on_insert_unique(&mut self, input: &I) -> T;
-    fn on_delete(&mut self, _row: NonZeroU32) {}
+    fn on_delete(&mut self, _row: R) {}
 
     /// Creates a new row and assigns a value to it.
-    fn insert(&mut self, value: &I) -> NonZeroU32
+    /// # Panics
+    /// Panics if the row following the last row does not fit in R.
+    fn insert(&mut self, value: &I) -> R
     where
         T: Clone,
     {
-        let row = unsafe { NonZeroU32::new_unchecked(self.as_ref().rows_count() + 1) };
+        let row = count_index(self.as_ref().rows_count())
+            .checked_add(1)
+            .and_then(R::from_index)
+            .expect("row overflow: the triee has no free row to insert");
         self.update(row, value);
         row
     }
 
     /// Updates the value in the specified row.
-    fn update(&mut self, row: NonZeroU32, value: &I)
+    fn update(&mut self, row: R, value: &I)
     where
         T: Clone,
     {
@@ -41,12 +50,16 @@ pub trait AvltrieeUpdate<T, I: ?Sized, A: AvltrieeAllocator<T>>:
 
             triee.allocate(row);
 
+            let new_node = unsafe { triee.allocator.node_mut_ptr(row.index()) };
             let same_node = unsafe { triee.node_unchecked_mut(same_row) };
             let same_left = same_node.left;
             let same_right = same_node.right;
             let same_parent = same_node.parent;
 
-            *unsafe { triee.node_unchecked_mut(row) } = same_node.same_clone(same_row, row);
+            same_node.same_clone(row, unsafe { &mut *new_node });
+            unsafe { triee.set_same_unchecked(row, Some(same_row)) };
+            *unsafe { triee.stored_value_unchecked_mut(row) } =
+                unsafe { triee.stored_value_unchecked(same_row) }.clone();
 
             triee.replace_child(same_parent, same_row, Some(row));
 
@@ -56,6 +69,9 @@ pub trait AvltrieeUpdate<T, I: ?Sized, A: AvltrieeAllocator<T>>:
             if let Some(right) = same_right {
                 unsafe { triee.node_unchecked_mut(right) }.parent = Some(row);
             }
+
+            triee.count_up(row);
+            triee.set_len(triee.len() + R::Count::from(1));
         } else {
             let value = self.convert_on_insert_unique(value);
             unsafe { self.as_mut().insert_unique_unchecked(row, value, edge) };
@@ -63,21 +79,118 @@ pub trait AvltrieeUpdate<T, I: ?Sized, A: AvltrieeAllocator<T>>:
     }
 
     /// Delete the specified row.
-    fn delete(&mut self, row: NonZeroU32) {
+    fn delete(&mut self, row: R) {
+        // The head is looked up before on_delete, which may discard the value.
+        let head = self.value(row).map(|value| {
+            self.as_ref()
+                .same_head(row)
+                .unwrap_or_else(|| self.row(value).unwrap())
+        });
         self.on_delete(row);
-        self.as_mut().delete_inner(row);
+        if let Some(head) = head {
+            self.as_mut().delete_inner(row, head);
+        }
+    }
+
+    /// Deletes all rows with the specified range of values and returns them in ascending order of value.
+    /// on_delete is called for each row.
+    fn delete_range(&mut self, start: &I, end: &I) -> Vec<R>
+    where
+        Self: Sized,
+    {
+        let rows = AvltrieeIter::range_asc(self, start, end).collect();
+        delete_rows(self, rows)
+    }
+
+    /// Deletes all rows with values less than the specified value and returns them in ascending order of value.
+    /// on_delete is called for each row.
+    fn delete_under(&mut self, value: &I) -> Vec<R>
+    where
+        Self: Sized,
+    {
+        let rows = AvltrieeIter::under_asc(self, value).collect();
+        delete_rows(self, rows)
+    }
+
+    /// Discards all links and reconstructs a balanced tree from the values of live rows.
+    /// Row numbers are preserved. Use to recover when [AvltrieeSearch::validate] fails.
+    fn rebuild(&mut self) {
+        let triee = self.as_ref();
+        let mut rows: Vec<R> = (1..=count_index(triee.rows_count()))
+            .filter_map(R::from_index)
+            .filter(|&row| triee.node(row).is_some())
+            .collect();
+        rows.sort_by(|a, b| {
+            Self::cmp(unsafe { self.value_unchecked(*a) }, unsafe {
+                self.value_unchecked(*b)
+            })
+        });
+
+        let mut groups = vec![];
+        let mut start = 0;
+        for i in 1..=rows.len() {
+            if i == rows.len()
+                || Self::cmp(unsafe { self.value_unchecked(rows[start]) }, unsafe {
+                    self.value_unchecked(rows[i])
+                }) != Ordering::Equal
+            {
+                groups.push(start..i);
+                start = i;
+            }
+        }
+
+        self.as_mut().rebuild_sorted(&rows, &groups);
+    }
+}
+
+/// Deletes rows given in the order of iteration, where each value starts with the head of its same branch.
+/// If at least half of the live rows are deleted, the remaining rows are rebuilt instead of rebalancing per row.
+/// Either way the rows are discarded in the same order, so rows_count ends up the same.
+fn delete_rows<S, T, I, A, R>(s: &mut S, rows: Vec<R>) -> Vec<R>
+where
+    S: AvltrieeUpdate<T, I, A, R>,
+    I: ?Sized,
+    A: AvltrieeAllocator<T, R>,
+    R: AvltrieeRow,
+{
+    let rebuild = rows.len() * 2 >= count_index(s.as_ref().len());
+
+    let mut start = 0;
+    for i in 1..=rows.len() {
+        if i == rows.len()
+            || S::cmp(unsafe { s.value_unchecked(rows[start]) }, unsafe {
+                s.value_unchecked(rows[i])
+            }) != Ordering::Equal
+        {
+            // Deleting from the end of the same branch keeps the head in place.
+            let head = rows[start];
+            for &row in rows[start..i].iter().rev() {
+                s.on_delete(row);
+                if rebuild {
+                    s.as_mut().discard(row);
+                } else {
+                    s.as_mut().delete_inner(row, head);
+                }
+            }
+            start = i;
+        }
     }
+    if rebuild {
+        s.rebuild();
+    }
+    rows
 }
 
-impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
+impl<T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> Avltriee<T, I, A, R> {
     /// Insert a unique value.
     /// If you specify a row that does not exist, space will be automatically allocated. If you specify a row that is too large, memory may be allocated unnecessarily.
     /// # Safety
     /// value ​​must be unique.
-    pub unsafe fn insert_unique_unchecked(&mut self, row: NonZeroU32, value: T, edge: Edge) {
+    pub unsafe fn insert_unique_unchecked(&mut self, row: R, value: T, edge: Edge<R>) {
         self.allocate(row);
 
-        *self.node_unchecked_mut(row) = AvltrieeNode::new(edge.0, value);
+        self.node_unchecked_mut(row).init(edge.0);
+        *self.stored_value_unchecked_mut(row) = value;
         if let Some(found_row) = edge.0 {
             let p = self.node_unchecked_mut(found_row);
             if edge.1 == Ordering::Greater {
@@ -85,34 +198,70 @@ impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
             } else {
                 p.right = Some(row);
             }
+            self.count_up(found_row);
             self.balance(row);
         } else {
             self.set_root(Some(row));
         }
+        self.set_len(self.len() + R::Count::from(1));
     }
 
-    fn reset_height(&mut self, row: NonZeroU32) {
+    fn reset_height(&mut self, row: R) {
         let node = unsafe { self.node_unchecked(row) };
 
-        let left_height = node.left.map_or(
-            0,
-            |left: NonZeroU32| unsafe { self.node_unchecked(left) }.height,
-        );
+        let left_height = node
+            .left
+            .map_or(0, |left: R| unsafe { self.node_unchecked(left) }.height());
 
         let right_height = node
             .right
-            .map_or(0, |right| unsafe { self.node_unchecked(right) }.height);
+            .map_or(0, |right| unsafe { self.node_unchecked(right) }.height());
+
+        unsafe { self.node_unchecked_mut(row) }
+            .set_height(std::cmp::max(left_height, right_height) + 1);
+    }
+
+    pub(crate) fn count(&self, row: Option<R>) -> R::Count {
+        row.map_or(R::Count::default(), |row| {
+            unsafe { self.node_unchecked(row) }.count()
+        })
+    }
+
+    /// Returns the head row of the same branch to which the specified row belongs.
+    /// Gives up and returns None if the head is farther than the height of the triee,
+    /// since searching by value is cheaper than walking a long same branch.
+    fn same_head(&self, row: R) -> Option<R> {
+        let mut row = row;
+        for _ in 0..=self.height(self.root()) {
+            match unsafe { self.node_unchecked(row) }.parent {
+                Some(parent) if unsafe { self.same_unchecked(parent) } == Some(row) => {
+                    row = parent;
+                }
+                _ => return Some(row),
+            }
+        }
+        None
+    }
 
-        unsafe { self.node_unchecked_mut(row) }.height =
-            std::cmp::max(left_height, right_height) + 1;
+    fn count_up(&mut self, row: R) {
+        let mut row = Some(row);
+        while let Some(row_inner) = row {
+            let node = unsafe { self.node_unchecked_mut(row_inner) };
+            node.set_count(node.count() + R::Count::from(1));
+            row = node.parent;
+        }
+    }
+
+    fn count_down(&mut self, row: R) {
+        let mut row = Some(row);
+        while let Some(row_inner) = row {
+            let node = unsafe { self.node_unchecked_mut(row_inner) };
+            node.set_count(node.count() - R::Count::from(1));
+            row = node.parent;
+        }
     }
 
-    fn replace_child(
-        &mut self,
-        parent: Option<NonZeroU32>,
-        current_child: NonZeroU32,
-        new_child: Option<NonZeroU32>,
-    ) {
+    fn replace_child(&mut self, parent: Option<R>, current_child: R, new_child: Option<R>) {
         if let Some(parent) = parent {
             unsafe { self.node_unchecked_mut(parent) }.changeling(current_child, new_child);
         } else {
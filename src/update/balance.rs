@@ -1,11 +1,17 @@
-use std::num::NonZeroU32;
+use crate::{Avltriee, AvltrieeAllocator, AvltrieeRow};
 
-use crate::{Avltriee, AvltrieeAllocator, AvltrieeNode};
+impl<T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> Avltriee<T, I, A, R> {
+    /// Rebalances the ancestors of the specified row.
+    pub(crate) fn balance(&mut self, row: R) {
+        if let Some(parent) = unsafe { self.node_unchecked(row) }.parent {
+            self.rebalance(parent);
+        }
+    }
 
-impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
-    pub(crate) fn balance(&mut self, row: NonZeroU32) {
-        let mut t: &AvltrieeNode<T> = unsafe { self.node_unchecked(row) };
-        while let Some(mut u_row) = t.parent {
+    /// Rebalances the specified row and its ancestors.
+    pub(crate) fn rebalance(&mut self, row: R) {
+        let mut u_row = row;
+        loop {
             let u = unsafe { self.node_unchecked(u_row) };
 
             let left_height = self.height(u.left);
@@ -19,7 +25,7 @@ impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
                         self.rotate_left(t_row);
                     }
                     self.rotate_right(u_row);
-                    u_row = t_row;
+                    u_row = unsafe { self.node_unchecked(u_row) }.parent.unwrap();
                 }
                 -2 => {
                     let t_row = u.right.unwrap();
@@ -28,25 +34,29 @@ impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
                         self.rotate_right(t_row);
                     }
                     self.rotate_left(u_row);
-                    u_row = t_row;
+                    u_row = unsafe { self.node_unchecked(u_row) }.parent.unwrap();
                 }
                 _ => {
                     let new_height = std::cmp::max(left_height, right_height) + 1;
-                    if u.height == new_height {
+                    if u.height() == new_height {
                         break;
                     }
-                    unsafe { self.node_unchecked_mut(u_row) }.height = new_height;
+                    unsafe { self.node_unchecked_mut(u_row) }.set_height(new_height);
                 }
             };
-            t = unsafe { self.node_unchecked(u_row) };
+            if let Some(parent) = unsafe { self.node_unchecked(u_row) }.parent {
+                u_row = parent;
+            } else {
+                break;
+            }
         }
     }
 
-    fn height(&self, row: Option<NonZeroU32>) -> u8 {
-        row.map_or(0, |row| unsafe { self.node_unchecked(row) }.height)
+    pub(crate) fn height(&self, row: Option<R>) -> u8 {
+        row.map_or(0, |row| unsafe { self.node_unchecked(row) }.height())
     }
 
-    fn rotate_common(&mut self, row: NonZeroU32, child_row: NonZeroU32) {
+    fn rotate_common(&mut self, row: R, child_row: R) {
         let node_parent = unsafe { self.node_unchecked(row) }.parent;
 
         self.replace_child(node_parent, row, Some(child_row));
@@ -58,10 +68,15 @@ impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
         unsafe { self.node_unchecked_mut(row) }.parent = Some(child_row);
     }
 
-    fn rotate_left(&mut self, row: NonZeroU32) {
+    fn rotate_left(&mut self, row: R) {
         let right_row = unsafe { self.node_unchecked(row) }.right.unwrap();
         let right_left = unsafe { self.node_unchecked(right_row) }.left;
 
+        let count = unsafe { self.node_unchecked(row) }.count();
+        let row_count = count - self.count(Some(right_row)) + self.count(right_left);
+        unsafe { self.node_unchecked_mut(row) }.set_count(row_count);
+        unsafe { self.node_unchecked_mut(right_row) }.set_count(count);
+
         unsafe { self.node_unchecked_mut(row) }.right = right_left;
         if let Some(right) = right_left {
             unsafe { self.node_unchecked_mut(right) }.parent = Some(row);
@@ -71,10 +86,15 @@ impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
         self.rotate_common(row, right_row);
     }
 
-    fn rotate_right(&mut self, row: NonZeroU32) {
+    fn rotate_right(&mut self, row: R) {
         let left_row = unsafe { self.node_unchecked(row) }.left.unwrap();
         let left_right = unsafe { self.node_unchecked(left_row) }.right;
 
+        let count = unsafe { self.node_unchecked(row) }.count();
+        let row_count = count - self.count(Some(left_row)) + self.count(left_right);
+        unsafe { self.node_unchecked_mut(row) }.set_count(row_count);
+        unsafe { self.node_unchecked_mut(left_row) }.set_count(count);
+
         unsafe { self.node_unchecked_mut(row) }.left = left_right;
         if let Some(left) = left_right {
             unsafe { self.node_unchecked_mut(left) }.parent = Some(row);
@@ -1,21 +1,21 @@
-use std::num::NonZeroU32;
+use crate::{Avltriee, AvltrieeAllocator, AvltrieeRow};
 
-use crate::{Avltriee, AvltrieeAllocator};
-
-impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
-    fn delete_same(&mut self, delete_row: NonZeroU32) {
+impl<T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> Avltriee<T, I, A, R> {
+    fn delete_same(&mut self, delete_row: R) {
         let delete_node = unsafe { self.node_unchecked(delete_row) };
 
-        let delete_node_same = delete_node.same.unwrap();
+        let delete_node_same = unsafe { self.same_unchecked(delete_row) }.unwrap();
         let delete_node_parent = delete_node.parent;
-        let delete_node_height = delete_node.height;
+        let delete_node_height = delete_node.height();
+        let delete_node_count = delete_node.count();
         let delete_node_left = delete_node.left;
         let delete_node_right = delete_node.right;
 
         let new_node = unsafe { self.node_unchecked_mut(delete_node_same) };
 
         new_node.parent = delete_node_parent;
-        new_node.height = delete_node_height;
+        new_node.set_height(delete_node_height);
+        new_node.set_count(delete_node_count);
         new_node.left = delete_node_left;
         new_node.right = delete_node_right;
 
@@ -27,16 +27,27 @@ impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
         }
     }
 
-    fn delete_intermediate(&mut self, delete_row: NonZeroU32) -> (NonZeroU32, NonZeroU32) {
+    fn delete_intermediate(&mut self, delete_row: R) -> (R, R) {
         let delete_node = unsafe { self.node_unchecked(delete_row) };
 
         let delete_node_left = delete_node.left;
         let delete_node_right = delete_node.right;
         let delete_node_parent = delete_node.parent;
-        let delete_node_height = delete_node.height;
+        let delete_node_height = delete_node.height();
+        let delete_node_count = delete_node.count();
 
         let left_max = self.max(delete_node_left).unwrap();
 
+        let left_max_node = unsafe { self.node_unchecked(left_max) };
+        let left_max_same_count = left_max_node.count() - self.count(left_max_node.left);
+        let mut parent = left_max_node.parent;
+        while let Some(parent_inner) = parent.filter(|&p| p != delete_row) {
+            let node = unsafe { self.node_unchecked_mut(parent_inner) };
+            node.set_count(node.count() - left_max_same_count);
+            parent = node.parent;
+        }
+        unsafe { self.node_unchecked_mut(left_max) }.set_count(delete_node_count);
+
         unsafe { self.node_unchecked_mut(left_max) }.right = delete_node_right;
         unsafe { self.node_unchecked_mut(delete_node_right.unwrap()) }.parent = Some(left_max);
 
@@ -50,7 +61,7 @@ impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
             let left_max_parent = left_max_node.parent.unwrap();
             let left_max_left = left_max_node.left;
 
-            left_max_node.height = delete_node_height;
+            left_max_node.set_height(delete_node_height);
             left_max_node.left = delete_node_left;
             unsafe { self.node_unchecked_mut(delete_node_left.unwrap()) }.parent = Some(left_max);
 
@@ -63,14 +74,18 @@ impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
         }
     }
 
-    pub(crate) fn delete_inner(&mut self, row: NonZeroU32) {
-        if let Some(node) = self.node(row) {
+    /// Deletes the row. head is the head row of the same branch to which the row belongs.
+    pub(crate) fn delete_inner(&mut self, row: R, head: R) {
+        if self.node(row).is_some() {
+            self.count_down(head);
+
+            let node = unsafe { self.node_unchecked(row) };
             let row_parent = node.parent;
-            let same = node.same;
+            let same = unsafe { self.same_unchecked(row) };
             if let Some(row_parent_inner) = row_parent {
-                let parent_same = unsafe { self.node_unchecked_mut(row_parent_inner) }.same;
+                let parent_same = unsafe { self.same_unchecked(row_parent_inner) };
                 if parent_same == Some(row) {
-                    unsafe { self.node_unchecked_mut(row_parent_inner) }.same = same;
+                    unsafe { self.set_same_unchecked(row_parent_inner, same) };
                     if same.is_some() {
                         self.delete_same(row);
                     }
@@ -86,21 +101,21 @@ impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
                         if let Some(right) = right {
                             unsafe { self.node_unchecked_mut(right) }.parent = row_parent;
                         }
-                        self.balance(row_parent_inner);
-                    } else if right.is_none() {
-                        unsafe { self.node_unchecked_mut(row_parent_inner) }.changeling(row, left);
-                        unsafe { self.node_unchecked_mut(left.unwrap()) }.parent = row_parent;
-                        self.balance(row_parent_inner);
+                        self.rebalance(row_parent_inner);
+                    } else if let (Some(left), None) = (left, right) {
+                        unsafe { self.node_unchecked_mut(row_parent_inner) }
+                            .changeling(row, Some(left));
+                        unsafe { self.node_unchecked_mut(left) }.parent = row_parent;
+                        self.rebalance(row_parent_inner);
                     } else {
                         let (new_row, balance_row) = self.delete_intermediate(row);
                         unsafe { self.node_unchecked_mut(row_parent_inner) }
                             .changeling(row, Some(new_row));
-                        let delete_row_height = unsafe { self.node_unchecked(row) }.height;
+                        let delete_row_height = unsafe { self.node_unchecked(row) }.height();
                         let node = unsafe { self.node_unchecked_mut(new_row) };
-                        node.height = delete_row_height;
+                        node.set_height(delete_row_height);
                         node.parent = row_parent;
-                        self.reset_height(balance_row);
-                        self.balance(balance_row);
+                        self.rebalance(balance_row);
                     }
                 }
             } else {
@@ -115,34 +130,40 @@ impl<T, I: ?Sized, A: AvltrieeAllocator<T>> Avltriee<T, I, A> {
                             unsafe { self.node_unchecked_mut(right) }.parent = None;
                         }
                         self.set_root(right);
-                    } else if right.is_none() {
-                        unsafe { self.node_unchecked_mut(left.unwrap()) }.parent = None;
-                        self.set_root(left);
+                    } else if let (Some(left), None) = (left, right) {
+                        unsafe { self.node_unchecked_mut(left) }.parent = None;
+                        self.set_root(Some(left));
                     } else {
                         let (new_row, balance_row) = self.delete_intermediate(row);
                         self.set_root(Some(new_row));
                         unsafe { self.node_unchecked_mut(new_row) }.parent = None;
-                        self.reset_height(balance_row);
-                        self.balance(balance_row);
+                        self.rebalance(balance_row);
                     }
                 }
             }
-            unsafe { self.node_unchecked_mut(row) }.height = 0;
-
-            if row.get() == self.rows_count() {
-                let mut current = row.get() - 1;
-                if current > 0 {
-                    while unsafe { self.allocator.get(NonZeroU32::new_unchecked(current)) }
-                        .is_none()
-                    {
-                        current -= 1;
-                        if current == 0 {
-                            break;
-                        }
-                    }
-                }
-                self.set_rows_count(current);
+            self.discard(row);
+        }
+    }
+
+    /// Marks the row as deleted without touching the links of other rows.
+    /// The row must already be unlinked, or the triee must be rebuilt afterwards.
+    pub(crate) fn discard(&mut self, row: R) {
+        unsafe { self.set_same_unchecked(row, None) };
+        unsafe { self.node_unchecked_mut(row) }.set_height(0);
+        self.set_len(self.len() - R::Count::from(1));
+
+        if row.get() == self.rows_count() {
+            let zero = R::Count::default();
+            let mut current = row.get() - R::Count::from(1);
+            while current > zero
+                && self
+                    .allocator
+                    .get(unsafe { R::new_unchecked(current) })
+                    .is_none()
+            {
+                current -= R::Count::from(1);
             }
+            self.set_rows_count(current);
         }
     }
 }
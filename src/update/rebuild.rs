@@ -0,0 +1,55 @@
+use std::ops::Range;
+
+use crate::{row::index_count, Avltriee, AvltrieeAllocator, AvltrieeRow};
+
+impl<T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> Avltriee<T, I, A, R> {
+    /// Links rows sorted by value into a balanced tree.
+    /// groups are ranges of rows having the same value.
+    pub(crate) fn rebuild_sorted(&mut self, rows: &[R], groups: &[Range<usize>]) {
+        let root = self.rebuild_subtree(rows, groups, None);
+        self.set_root(root);
+        self.set_len(index_count(rows.len()));
+    }
+
+    fn rebuild_subtree(
+        &mut self,
+        rows: &[R],
+        groups: &[Range<usize>],
+        parent: Option<R>,
+    ) -> Option<R> {
+        if groups.is_empty() {
+            return None;
+        }
+        let mid = groups.len() / 2;
+        let same_rows = &rows[groups[mid].clone()];
+        let row = same_rows[0];
+
+        let left = self.rebuild_subtree(rows, &groups[..mid], Some(row));
+        let right = self.rebuild_subtree(rows, &groups[mid + 1..], Some(row));
+
+        let height = std::cmp::max(self.height(left), self.height(right)) + 1;
+        let count = self.count(left) + self.count(right) + index_count(same_rows.len());
+
+        let node = unsafe { self.node_unchecked_mut(row) };
+        node.parent = parent;
+        node.left = left;
+        node.right = right;
+        node.set_height(height);
+        node.set_count(count);
+
+        let mut same_parent = row;
+        for &same_row in &same_rows[1..] {
+            unsafe { self.set_same_unchecked(same_parent, Some(same_row)) };
+            let same_node = unsafe { self.node_unchecked_mut(same_row) };
+            same_node.parent = Some(same_parent);
+            same_node.left = None;
+            same_node.right = None;
+            same_node.set_height(1);
+            same_node.set_count(R::Count::from(1));
+            same_parent = same_row;
+        }
+        unsafe { self.set_same_unchecked(same_parent, None) };
+
+        Some(row)
+    }
+}
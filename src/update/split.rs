@@ -0,0 +1,260 @@
+use std::cmp::Ordering;
+
+use crate::{row::index_count, Avltriee, AvltrieeAllocator, AvltrieeRow, AvltrieeSearch};
+
+impl<T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow> Avltriee<T, I, A, R> {
+    /// Moves all rows with values greater than or equal to the specified value into a new triee.
+    /// Row numbers are preserved, and rows_count of this triee is kept, so [Avltriee::insert] does not reuse the moved rows.
+    /// The links are split in O(log n), then the k moved rows are copied to the new allocator in O(k).
+    pub fn split_off(&mut self, value: &I) -> Self
+    where
+        Self: AvltrieeSearch<T, I, A, R>,
+        A: Default,
+    {
+        let (left, right) = self.split_subtree(self.root(), value);
+        self.set_root(left);
+        if let Some(left) = left {
+            unsafe { self.node_unchecked_mut(left) }.parent = None;
+        }
+
+        let mut other = Self::with_allocator(A::default());
+        let rows = self.subtree_rows(right);
+        self.move_rows(&mut other, &rows);
+        other.set_root(right);
+        if let Some(right) = right {
+            unsafe { other.node_unchecked_mut(right) }.parent = None;
+        }
+        other
+    }
+
+    /// Moves all rows of other into this triee. Leaves other empty.
+    /// Row numbers are preserved. The links are joined in O(log n), after the k moved rows are copied to this allocator in O(k).
+    /// # Panics
+    /// Panics if the values of the two triees overlap, or if a row of other is already used in this triee.
+    pub fn append(&mut self, other: &mut Self)
+    where
+        Self: AvltrieeSearch<T, I, A, R>,
+    {
+        let (Some(root), Some(other_root)) = (self.root(), other.root()) else {
+            if self.root().is_none() {
+                let rows = other.subtree_rows(other.root());
+                let root = other.root();
+                other.move_rows(self, &rows);
+                other.set_root(None);
+                self.set_root(root);
+            }
+            return;
+        };
+
+        let boundary = |lower: &Self, upper: &Self| {
+            Self::cmp(
+                unsafe { lower.value_unchecked(lower.max(lower.root()).unwrap()) },
+                unsafe { upper.value_unchecked(upper.min(upper.root()).unwrap()) },
+            ) == Ordering::Less
+        };
+        let self_lower = boundary(self, other);
+        assert!(
+            self_lower || boundary(other, self),
+            "values of the triees overlap"
+        );
+
+        let rows = other.subtree_rows(Some(other_root));
+        assert!(
+            rows.iter().all(|&row| self.node(row).is_none()),
+            "row is already used in the triee"
+        );
+        other.move_rows(self, &rows);
+        other.set_root(None);
+
+        let root = if self_lower {
+            self.join2(Some(root), Some(other_root))
+        } else {
+            self.join2(Some(other_root), Some(root))
+        };
+        self.set_root(root);
+        if let Some(root) = root {
+            unsafe { self.node_unchecked_mut(root) }.parent = None;
+        }
+    }
+
+    /// Splits the subtree into the rows less than the value and the rest. Returns the roots of both.
+    fn split_subtree(&mut self, row: Option<R>, value: &I) -> (Option<R>, Option<R>)
+    where
+        Self: AvltrieeSearch<T, I, A, R>,
+    {
+        let Some(row) = row else {
+            return (None, None);
+        };
+        let node = unsafe { self.node_unchecked(row) };
+        let (left, right) = (node.left, node.right);
+        let same = self.same_count(row);
+        if Self::cmp(unsafe { self.value_unchecked(row) }, value) == Ordering::Less {
+            let (less, rest) = self.split_subtree(right, value);
+            (Some(self.join(left, row, same, less)), rest)
+        } else {
+            let (less, rest) = self.split_subtree(left, value);
+            (less, Some(self.join(rest, row, same, right)))
+        }
+    }
+
+    /// Joins two subtrees where all values of left are less than those of right. Returns the new root.
+    fn join2(&mut self, left: Option<R>, right: Option<R>) -> Option<R> {
+        let Some(left) = left else {
+            return right;
+        };
+        let (rest, max, same) = self.split_last(left);
+        Some(self.join(rest, max, same, right))
+    }
+
+    /// Detaches the node with the maximum value from the subtree.
+    /// Returns the root of the rest, the detached row and the number of rows in its same branch.
+    fn split_last(&mut self, row: R) -> (Option<R>, R, R::Count) {
+        let node = unsafe { self.node_unchecked(row) };
+        let (left, right) = (node.left, node.right);
+        let same = self.same_count(row);
+        match right {
+            None => (left, row, same),
+            Some(right) => {
+                let (rest, max, max_same) = self.split_last(right);
+                (Some(self.join(left, row, same, rest)), max, max_same)
+            }
+        }
+    }
+
+    /// Joins left, the middle row and right into a balanced subtree. Returns the new root.
+    /// same is the number of rows in the same branch of the middle row.
+    fn join(&mut self, left: Option<R>, middle: R, same: R::Count, right: Option<R>) -> R {
+        let (left_height, right_height) = (self.height(left), self.height(right));
+        if left_height > right_height + 1 {
+            let left = left.unwrap();
+            let node = unsafe { self.node_unchecked(left) };
+            let (left_left, left_right) = (node.left, node.right);
+            let left_same = self.same_count(left);
+            let joined = self.join(left_right, middle, same, right);
+            self.link(left, left_left, Some(joined), left_same);
+            self.join_balance(left)
+        } else if right_height > left_height + 1 {
+            let right = right.unwrap();
+            let node = unsafe { self.node_unchecked(right) };
+            let (right_left, right_right) = (node.left, node.right);
+            let right_same = self.same_count(right);
+            let joined = self.join(left, middle, same, right_left);
+            self.link(right, Some(joined), right_right, right_same);
+            self.join_balance(right)
+        } else {
+            self.link(middle, left, right, same);
+            middle
+        }
+    }
+
+    /// Sets the children of the row and recomputes its height and count.
+    fn link(&mut self, row: R, left: Option<R>, right: Option<R>, same: R::Count) {
+        let height = std::cmp::max(self.height(left), self.height(right)) + 1;
+        let count = self.count(left) + self.count(right) + same;
+        let node = unsafe { self.node_unchecked_mut(row) };
+        node.left = left;
+        node.right = right;
+        node.set_height(height);
+        node.set_count(count);
+        for child in [left, right].into_iter().flatten() {
+            unsafe { self.node_unchecked_mut(child) }.parent = Some(row);
+        }
+    }
+
+    /// Rotates a subtree that is off balance by at most 2 after a join. Returns the new root.
+    fn join_balance(&mut self, row: R) -> R {
+        let node = unsafe { self.node_unchecked(row) };
+        let (left, right) = (node.left, node.right);
+        match self.height(left) as isize - self.height(right) as isize {
+            2 => {
+                let left = left.unwrap();
+                let left_node = unsafe { self.node_unchecked(left) };
+                if self.height(left_node.left) < self.height(left_node.right) {
+                    let same = self.same_count(row);
+                    let left = self.join_rotate_left(left);
+                    self.link(row, Some(left), right, same);
+                }
+                self.join_rotate_right(row)
+            }
+            -2 => {
+                let right = right.unwrap();
+                let right_node = unsafe { self.node_unchecked(right) };
+                if self.height(right_node.left) > self.height(right_node.right) {
+                    let same = self.same_count(row);
+                    let right = self.join_rotate_right(right);
+                    self.link(row, left, Some(right), same);
+                }
+                self.join_rotate_left(row)
+            }
+            _ => row,
+        }
+    }
+
+    fn join_rotate_left(&mut self, row: R) -> R {
+        let node = unsafe { self.node_unchecked(row) };
+        let (left, right) = (node.left, node.right.unwrap());
+        let right_node = unsafe { self.node_unchecked(right) };
+        let (right_left, right_right) = (right_node.left, right_node.right);
+        let (same, right_same) = (self.same_count(row), self.same_count(right));
+        self.link(row, left, right_left, same);
+        self.link(right, Some(row), right_right, right_same);
+        right
+    }
+
+    fn join_rotate_right(&mut self, row: R) -> R {
+        let node = unsafe { self.node_unchecked(row) };
+        let (left, right) = (node.left.unwrap(), node.right);
+        let left_node = unsafe { self.node_unchecked(left) };
+        let (left_left, left_right) = (left_node.left, left_node.right);
+        let (same, left_same) = (self.same_count(row), self.same_count(left));
+        self.link(row, left_right, right, same);
+        self.link(left, left_left, Some(row), left_same);
+        left
+    }
+
+    /// Returns all rows of the subtree, including the same branches.
+    fn subtree_rows(&self, row: Option<R>) -> Vec<R> {
+        let mut rows = vec![];
+        let mut stack: Vec<R> = row.into_iter().collect();
+        while let Some(row) = stack.pop() {
+            let node = unsafe { self.node_unchecked(row) };
+            stack.extend(node.left);
+            stack.extend(node.right);
+            let mut same = Some(row);
+            while let Some(same_row) = same {
+                rows.push(same_row);
+                same = unsafe { self.same_unchecked(same_row) };
+            }
+        }
+        rows
+    }
+
+    /// Copies the links and values of the rows to the other triee and removes them from this triee.
+    /// rows_count of this triee is kept.
+    fn move_rows(&mut self, other: &mut Self, rows: &[R]) {
+        if let Some(&max) = rows.iter().max() {
+            other.allocate(max);
+        }
+        for &row in rows {
+            unsafe {
+                other.set_same_unchecked(row, self.same_unchecked(row));
+                self.set_same_unchecked(row, None);
+            }
+            let node = unsafe { self.node_unchecked_mut(row) };
+            let other_node = unsafe { other.node_unchecked_mut(row) };
+            other_node.parent = node.parent;
+            other_node.left = node.left;
+            other_node.right = node.right;
+            other_node.set_height(node.height());
+            other_node.set_count(node.count());
+            node.set_height(0);
+            std::mem::swap(unsafe { self.stored_value_unchecked_mut(row) }, unsafe {
+                other.stored_value_unchecked_mut(row)
+            });
+        }
+
+        let moved = index_count(rows.len());
+        self.set_len(self.len() - moved);
+        other.set_len(other.len() + moved);
+    }
+}
@@ -0,0 +1,153 @@
+use std::{cmp::Ordering, fmt, num::NonZeroU32};
+
+use crate::{row::count_index, AvltrieeAllocator, AvltrieeRow, AvltrieeSearch};
+
+/// Structural inconsistency found by [AvltrieeSearch::validate].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AvltrieeValidationError<R = NonZeroU32> {
+    pub row: Option<R>,
+    pub message: &'static str,
+}
+
+impl<R: AvltrieeRow> fmt::Display for AvltrieeValidationError<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(row) = self.row {
+            write!(f, "row {}: {}", row, self.message)
+        } else {
+            write!(f, "{}", self.message)
+        }
+    }
+}
+
+impl<R: AvltrieeRow> std::error::Error for AvltrieeValidationError<R> {}
+
+fn error<V, R>(row: Option<R>, message: &'static str) -> Result<V, AvltrieeValidationError<R>> {
+    Err(AvltrieeValidationError { row, message })
+}
+
+struct Validator<'a, S: ?Sized> {
+    s: &'a S,
+    visited: Vec<bool>,
+    reached: usize,
+}
+
+impl<'a, S: ?Sized> Validator<'a, S> {
+    fn visit<R: AvltrieeRow>(&mut self, row: R) -> Result<(), AvltrieeValidationError<R>> {
+        match self.visited.get_mut(row.index()) {
+            None => error(Some(row), "link to unallocated row"),
+            Some(true) => error(Some(row), "row is linked twice"),
+            Some(visited) => {
+                *visited = true;
+                self.reached += 1;
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns height and count of the subtree.
+    /// Every value of the subtree must be greater than the value of lower and less than the value of upper.
+    fn subtree<T, I: ?Sized, A: AvltrieeAllocator<T, R>, R: AvltrieeRow>(
+        &mut self,
+        row: Option<R>,
+        parent: Option<R>,
+        lower: Option<R>,
+        upper: Option<R>,
+    ) -> Result<(u8, R::Count), AvltrieeValidationError<R>>
+    where
+        S: AvltrieeSearch<T, I, A, R>,
+    {
+        let Some(row) = row else {
+            return Ok((0, R::Count::default()));
+        };
+        self.visit(row)?;
+        let triee = self.s.as_ref();
+        let (node, value) = unsafe { self.s.node_value_unchecked(row) };
+        if node.height() == 0 {
+            return error(Some(row), "link to deleted row");
+        }
+        if node.parent != parent {
+            return error(Some(row), "parent does not match");
+        }
+        for (bound, ord, message) in [
+            (
+                lower,
+                Ordering::Greater,
+                "value is not greater than an ancestor",
+            ),
+            (upper, Ordering::Less, "value is not less than an ancestor"),
+        ] {
+            if let Some(bound) = bound {
+                if S::cmp(value, unsafe { self.s.value_unchecked(bound) }) != ord {
+                    return error(Some(row), message);
+                }
+            }
+        }
+
+        let mut same_count = R::Count::from(1);
+        let mut same_parent = row;
+        let mut same = unsafe { triee.same_unchecked(row) };
+        while let Some(same_row) = same {
+            self.visit(same_row)?;
+            let same_node = triee.node(same_row).ok_or(AvltrieeValidationError {
+                row: Some(same_row),
+                message: "link to deleted row",
+            })?;
+            if same_node.parent != Some(same_parent) {
+                return error(Some(same_row), "parent does not match");
+            }
+            if same_node.left.is_some() || same_node.right.is_some() {
+                return error(Some(same_row), "same branch has children");
+            }
+            if S::cmp(unsafe { self.s.value_unchecked(same_row) }, value) != Ordering::Equal {
+                return error(Some(same_row), "same branch value differs");
+            }
+            same_count += R::Count::from(1);
+            same_parent = same_row;
+            same = unsafe { triee.same_unchecked(same_row) };
+        }
+
+        let (left_height, left_count) = self.subtree(node.left, Some(row), lower, Some(row))?;
+        let (right_height, right_count) = self.subtree(node.right, Some(row), Some(row), upper)?;
+        if left_height.abs_diff(right_height) > 1 {
+            return error(Some(row), "unbalanced");
+        }
+        let height = std::cmp::max(left_height, right_height) + 1;
+        if node.height() != height {
+            return error(Some(row), "height does not match");
+        }
+        let count = left_count + right_count + same_count;
+        if node.count() != count {
+            return error(Some(row), "count does not match");
+        }
+        Ok((height, count))
+    }
+}
+
+pub(crate) fn validate<S, T, I, A, R>(s: &S) -> Result<(), AvltrieeValidationError<R>>
+where
+    S: AvltrieeSearch<T, I, A, R> + ?Sized,
+    I: ?Sized,
+    A: AvltrieeAllocator<T, R>,
+    R: AvltrieeRow,
+{
+    let triee = s.as_ref();
+    let rows_count = count_index(triee.rows_count());
+    let mut validator = Validator {
+        s,
+        visited: vec![false; rows_count + 1],
+        reached: 0,
+    };
+    validator.subtree(triee.root(), None, None, None)?;
+
+    let live = (1..=rows_count)
+        .filter_map(R::from_index)
+        .filter(|&row| triee.node(row).is_some())
+        .count();
+    if validator.reached != live {
+        return error(None, "live row is not reachable from root");
+    }
+    if count_index(triee.len()) != live {
+        return error(None, "len does not match live rows");
+    }
+    Ok(())
+}
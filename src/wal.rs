@@ -0,0 +1,306 @@
+use std::{
+    borrow::Borrow,
+    fs::{File, OpenOptions},
+    io::{self, BufReader, ErrorKind, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use crate::{row::count_index, AvltrieeAllocator, AvltrieeRow, AvltrieeUpdate};
+
+const OP_UPDATE: u8 = 1;
+const OP_DELETE: u8 = 2;
+
+/// Bytes of the operation and the row at the start of each record.
+const HEADER_LEN: usize = 9;
+/// Bytes of the checksum at the end of each record.
+const CHECKSUM_LEN: usize = 4;
+
+/// Value that can be recorded in the [AvltrieeWal].
+pub trait AvltrieeWalValue {
+    type Owned: Borrow<Self>;
+
+    fn encode(&self, buf: &mut Vec<u8>);
+
+    /// Returns None if the bytes are not an encoded value.
+    fn decode(bytes: &[u8]) -> Option<Self::Owned>;
+}
+
+macro_rules! impl_wal_value {
+    ($($t:ty),*) => {
+        $(
+            impl AvltrieeWalValue for $t {
+                type Owned = $t;
+
+                fn encode(&self, buf: &mut Vec<u8>) {
+                    buf.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn decode(bytes: &[u8]) -> Option<Self::Owned> {
+                    bytes.try_into().ok().map(<$t>::from_le_bytes)
+                }
+            }
+        )*
+    };
+}
+impl_wal_value!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+impl AvltrieeWalValue for [u8] {
+    type Owned = Vec<u8>;
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self);
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self::Owned> {
+        Some(bytes.to_vec())
+    }
+}
+
+impl AvltrieeWalValue for str {
+    type Owned = String;
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self::Owned> {
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+}
+
+/// When the [AvltrieeWal] calls fsync.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AvltrieeWalSync {
+    /// Sync after every operation.
+    Always,
+    /// Sync after the specified number of operations.
+    Every(u32),
+    /// Sync only when [AvltrieeWal::sync] is called.
+    Manual,
+}
+
+/// Write-ahead log of update and delete operations.
+/// Each operation is recorded with a checksum before it is applied to the triee.
+/// A crash while an operation is applied can leave the links of the triee broken,
+/// so [AvltrieeWal::replay] validates the triee and rebuilds it from the row values before replaying.
+/// Since operations assign a value to a row, replaying them over the rebuilt triee gives the same result.
+pub struct AvltrieeWal {
+    file: File,
+    sync: AvltrieeWalSync,
+    unsynced: u32,
+    buf: Vec<u8>,
+    /// Length of the log up to the last complete record. None if a failed write could not be undone.
+    len: Option<u64>,
+}
+
+impl AvltrieeWal {
+    /// Opens the log file. Creates it if it does not exist.
+    pub fn open<P: AsRef<Path>>(path: P, sync: AvltrieeWalSync) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+        let len = file.metadata()?.len();
+        Ok(Self {
+            file,
+            sync,
+            unsynced: 0,
+            buf: vec![],
+            len: Some(len),
+        })
+    }
+
+    /// Applies all recorded operations to the triee.
+    /// If [validate](crate::AvltrieeSearch::validate) fails, the triee is rebuilt with [AvltrieeUpdate::rebuild] first.
+    /// A torn or corrupted record, left by a crash while writing, is discarded along with everything after it.
+    /// Returns InvalidData if an intact record does not fit the row or value type of the triee.
+    pub fn replay<S, T, I, A, R>(&mut self, triee: &mut S) -> io::Result<()>
+    where
+        S: AvltrieeUpdate<T, I, A, R>,
+        T: Clone,
+        I: ?Sized + AvltrieeWalValue,
+        A: AvltrieeAllocator<T, R>,
+        R: AvltrieeRow,
+    {
+        if triee.validate().is_err() {
+            triee.rebuild();
+        }
+
+        let file_len = self.file.seek(SeekFrom::End(0))?;
+        self.file.seek(SeekFrom::Start(0))?;
+        let mut reader = BufReader::new(&self.file);
+        let mut complete = 0;
+        let mut record = vec![];
+        while read_record(&mut reader, &mut record, file_len - complete)? {
+            let row = usize::try_from(u64::from_le_bytes(record[1..9].try_into().unwrap()))
+                .ok()
+                .and_then(R::from_index)
+                .ok_or_else(|| io::Error::new(ErrorKind::InvalidData, "invalid row in log"))?;
+            if record[0] == OP_UPDATE {
+                let value = I::decode(&record[HEADER_LEN + 4..record.len() - CHECKSUM_LEN])
+                    .ok_or_else(|| {
+                        io::Error::new(ErrorKind::InvalidData, "invalid value in log")
+                    })?;
+                triee.update(row, value.borrow());
+            } else {
+                triee.delete(row);
+            }
+            complete += record.len() as u64;
+        }
+        self.file.set_len(complete)?;
+        self.len = Some(complete);
+        Ok(())
+    }
+
+    /// Records the update, then applies it to the triee.
+    /// If recording fails, the triee is left unchanged.
+    pub fn update<S, T, I, A, R>(&mut self, triee: &mut S, row: R, value: &I) -> io::Result<()>
+    where
+        S: AvltrieeUpdate<T, I, A, R>,
+        T: Clone,
+        I: ?Sized + AvltrieeWalValue,
+        A: AvltrieeAllocator<T, R>,
+        R: AvltrieeRow,
+    {
+        self.buf.clear();
+        self.buf.push(OP_UPDATE);
+        self.buf
+            .extend_from_slice(&(row.index() as u64).to_le_bytes());
+        self.buf.extend_from_slice(&[0; 4]);
+        value.encode(&mut self.buf);
+        let len = (self.buf.len() - HEADER_LEN - 4) as u32;
+        self.buf[HEADER_LEN..HEADER_LEN + 4].copy_from_slice(&len.to_le_bytes());
+        self.write()?;
+
+        triee.update(row, value);
+        Ok(())
+    }
+
+    /// Records the insertion, then applies it to the triee.
+    pub fn insert<S, T, I, A, R>(&mut self, triee: &mut S, value: &I) -> io::Result<R>
+    where
+        S: AvltrieeUpdate<T, I, A, R>,
+        T: Clone,
+        I: ?Sized + AvltrieeWalValue,
+        A: AvltrieeAllocator<T, R>,
+        R: AvltrieeRow,
+    {
+        let row = count_index(triee.as_ref().rows_count())
+            .checked_add(1)
+            .and_then(R::from_index)
+            .ok_or_else(|| io::Error::other("row overflow"))?;
+        self.update(triee, row, value)?;
+        Ok(row)
+    }
+
+    /// Records the deletion, then applies it to the triee.
+    /// If recording fails, the triee is left unchanged.
+    pub fn delete<S, T, I, A, R>(&mut self, triee: &mut S, row: R) -> io::Result<()>
+    where
+        S: AvltrieeUpdate<T, I, A, R>,
+        I: ?Sized,
+        A: AvltrieeAllocator<T, R>,
+        R: AvltrieeRow,
+    {
+        self.buf.clear();
+        self.buf.push(OP_DELETE);
+        self.buf
+            .extend_from_slice(&(row.index() as u64).to_le_bytes());
+        self.write()?;
+
+        triee.delete(row);
+        Ok(())
+    }
+
+    /// Flushes recorded operations to the storage.
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.unsynced = 0;
+        self.file.sync_data()
+    }
+
+    /// Discards all recorded operations. Call after the triee itself has been made durable.
+    pub fn checkpoint(&mut self) -> io::Result<()> {
+        self.file.set_len(0)?;
+        self.len = Some(0);
+        self.sync()
+    }
+
+    /// Appends the record in buf.
+    /// If it fails, the log is truncated back to the last complete record,
+    /// so that a torn record does not hide the records written after it from [AvltrieeWal::replay].
+    /// If even that fails, every later write returns an error.
+    fn write(&mut self) -> io::Result<()> {
+        let Some(len) = self.len else {
+            return Err(io::Error::other("log is broken by a failed write"));
+        };
+        let checksum = checksum(&self.buf);
+        self.buf.extend_from_slice(&checksum.to_le_bytes());
+        let result = self.file.write_all(&self.buf).and_then(|()| {
+            self.unsynced += 1;
+            match self.sync {
+                AvltrieeWalSync::Always => self.sync(),
+                AvltrieeWalSync::Every(n) if self.unsynced >= n => self.sync(),
+                _ => Ok(()),
+            }
+        });
+        self.len = match result {
+            Ok(()) => Some(len + self.buf.len() as u64),
+            Err(_) => self.file.set_len(len).ok().map(|()| len),
+        };
+        result
+    }
+}
+
+/// Reads the next record into record. remaining is the number of bytes left in the log.
+/// Returns false at the end of the log, or if the record is torn or corrupted.
+fn read_record<Rd: Read>(
+    reader: &mut Rd,
+    record: &mut Vec<u8>,
+    remaining: u64,
+) -> io::Result<bool> {
+    record.clear();
+    record.resize(HEADER_LEN, 0);
+    if !read_or_eof(reader, record)? {
+        return Ok(false);
+    }
+    let body_len = match record[0] {
+        OP_UPDATE => {
+            let mut len = [0; 4];
+            if !read_or_eof(reader, &mut len)? {
+                return Ok(false);
+            }
+            record.extend_from_slice(&len);
+            let len = u32::from_le_bytes(len) as u64;
+            if len > remaining.saturating_sub((HEADER_LEN + 4 + CHECKSUM_LEN) as u64) {
+                return Ok(false);
+            }
+            4 + len as usize
+        }
+        OP_DELETE => 0,
+        _ => return Ok(false),
+    };
+    let start = record.len();
+    record.resize(HEADER_LEN + body_len + CHECKSUM_LEN, 0);
+    if !read_or_eof(reader, &mut record[start..])? {
+        return Ok(false);
+    }
+    let (data, stored) = record.split_at(record.len() - CHECKSUM_LEN);
+    Ok(checksum(data) == u32::from_le_bytes(stored.try_into().unwrap()))
+}
+
+/// Fills buf. Returns false if the end of the log is reached first.
+fn read_or_eof<Rd: Read>(reader: &mut Rd, buf: &mut [u8]) -> io::Result<bool> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// FNV-1a hash of the record.
+fn checksum(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c9dc5, |hash, &byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    })
+}
@@ -0,0 +1,373 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    num::NonZeroU32,
+    ops::Bound,
+};
+
+use avltriee::{Avltriee, AvltrieeSearch, AvltrieeUpdate};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const SEEDS: u64 = 20;
+const STEPS: usize = 200;
+const MAX_ROW: u32 = 60;
+const MAX_VALUE: i64 = 10;
+
+/// Expected state: rows of each value, and value of each row.
+#[derive(Default)]
+struct Model {
+    map: BTreeMap<i64, BTreeSet<u32>>,
+    values: BTreeMap<u32, i64>,
+}
+
+impl Model {
+    fn update(&mut self, row: u32, value: i64) {
+        self.delete(row);
+        self.map.entry(value).or_default().insert(row);
+        self.values.insert(row, value);
+    }
+
+    fn delete(&mut self, row: u32) {
+        if let Some(value) = self.values.remove(&row) {
+            let rows = self.map.get_mut(&value).unwrap();
+            rows.remove(&row);
+            if rows.is_empty() {
+                self.map.remove(&value);
+            }
+        }
+    }
+
+    fn range(&self, start: Bound<i64>, end: Bound<i64>) -> Vec<(i64, &BTreeSet<u32>)> {
+        match (start, end) {
+            (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e))
+                if s > e =>
+            {
+                vec![]
+            }
+            (Bound::Excluded(s), Bound::Excluded(e)) if s == e => vec![],
+            _ => self
+                .map
+                .range((start, end))
+                .map(|(value, rows)| (*value, rows))
+                .collect(),
+        }
+    }
+
+    fn first(&self, start: Bound<i64>, end: Bound<i64>) -> Option<&BTreeSet<u32>> {
+        self.range(start, end).first().map(|(_, rows)| *rows)
+    }
+
+    fn last(&self, start: Bound<i64>, end: Bound<i64>) -> Option<&BTreeSet<u32>> {
+        self.range(start, end).last().map(|(_, rows)| *rows)
+    }
+}
+
+fn row(row: u32) -> NonZeroU32 {
+    NonZeroU32::new(row).unwrap()
+}
+
+/// Checks that rows come in the order of their values and that each value has exactly the expected rows.
+fn check_iter<It: Iterator<Item = NonZeroU32>>(
+    t: &Avltriee<i64>,
+    model: &Model,
+    iter: It,
+    start: Bound<i64>,
+    end: Bound<i64>,
+    desc: bool,
+    name: &str,
+) {
+    let mut expected = model.range(start, end);
+    if desc {
+        expected.reverse();
+    }
+    let got: Vec<(i64, u32)> = iter.map(|r| (*t.value(r).unwrap(), r.get())).collect();
+
+    let got_values: Vec<i64> = got.iter().map(|(value, _)| *value).collect();
+    let expected_values: Vec<i64> = expected
+        .iter()
+        .flat_map(|(value, rows)| std::iter::repeat_n(*value, rows.len()))
+        .collect();
+    assert_eq!(got_values, expected_values, "{name} values");
+
+    let got_rows: BTreeSet<(i64, u32)> = got.into_iter().collect();
+    let expected_rows: BTreeSet<(i64, u32)> = expected
+        .iter()
+        .flat_map(|(value, rows)| rows.iter().map(|row| (*value, *row)))
+        .collect();
+    assert_eq!(got_rows, expected_rows, "{name} rows");
+}
+
+fn check_found(found: Option<NonZeroU32>, expected: Option<&BTreeSet<u32>>, name: &str) {
+    match (found, expected) {
+        (Some(found), Some(rows)) => assert!(rows.contains(&found.get()), "{name} row {found}"),
+        (None, None) => {}
+        _ => panic!("{name}: {found:?} expected {expected:?}"),
+    }
+}
+
+fn check(t: &Avltriee<i64>, model: &Model) {
+    use Bound::{Excluded, Included, Unbounded};
+
+    t.validate().unwrap();
+    assert_eq!(t.len() as usize, model.values.len());
+
+    for (r, value) in &model.values {
+        assert_eq!(t.value(row(*r)), Some(value));
+    }
+    for r in 1..=MAX_ROW {
+        if !model.values.contains_key(&r) {
+            assert_eq!(t.value(row(r)), None);
+        }
+    }
+
+    check_iter(t, model, t.iter(), Unbounded, Unbounded, false, "iter");
+    check_iter(
+        t,
+        model,
+        t.desc_iter(),
+        Unbounded,
+        Unbounded,
+        true,
+        "desc_iter",
+    );
+
+    let distinct: Vec<(i64, u32)> = t
+        .iter_distinct()
+        .map(|(head, count)| (*t.value(head).unwrap(), count))
+        .collect();
+    let expected: Vec<(i64, u32)> = model
+        .map
+        .iter()
+        .map(|(value, rows)| (*value, rows.len() as u32))
+        .collect();
+    assert_eq!(distinct, expected, "iter_distinct");
+    let desc_distinct: Vec<(i64, u32)> = t
+        .desc_iter_distinct()
+        .map(|(head, count)| (*t.value(head).unwrap(), count))
+        .collect();
+    assert!(
+        desc_distinct.iter().eq(expected.iter().rev()),
+        "desc_iter_distinct"
+    );
+
+    let len = model.values.len();
+    for offset in [0, 1, len / 2, len, len + 1] {
+        assert!(t.iter_from_offset(offset as u32).eq(t.iter().skip(offset)));
+        assert!(t
+            .desc_iter_from_offset(offset as u32)
+            .eq(t.desc_iter().skip(offset)));
+    }
+
+    for v in -1..=MAX_VALUE + 1 {
+        check_found(t.row(&v), model.map.get(&v), "row");
+        check_found(t.ge(&v), model.first(Included(v), Unbounded), "ge");
+        check_found(t.gt(&v), model.first(Excluded(v), Unbounded), "gt");
+        check_found(t.le(&v), model.last(Unbounded, Included(v)), "le");
+        check_found(t.lt(&v), model.last(Unbounded, Excluded(v)), "lt");
+
+        let (by, by_end) = (Included(v), Included(v));
+        check_iter(t, model, t.iter_by(&v), by, by_end, false, "iter_by");
+        check_iter(
+            t,
+            model,
+            t.iter_from(&v),
+            Included(v),
+            Unbounded,
+            false,
+            "iter_from",
+        );
+        check_iter(
+            t,
+            model,
+            t.desc_iter_from(&v),
+            Included(v),
+            Unbounded,
+            true,
+            "desc_iter_from",
+        );
+        check_iter(
+            t,
+            model,
+            t.iter_to(&v),
+            Unbounded,
+            Included(v),
+            false,
+            "iter_to",
+        );
+        check_iter(
+            t,
+            model,
+            t.desc_iter_to(&v),
+            Unbounded,
+            Included(v),
+            true,
+            "desc_iter_to",
+        );
+        check_iter(
+            t,
+            model,
+            t.iter_over(&v),
+            Excluded(v),
+            Unbounded,
+            false,
+            "iter_over",
+        );
+        check_iter(
+            t,
+            model,
+            t.desc_iter_over(&v),
+            Excluded(v),
+            Unbounded,
+            true,
+            "desc_iter_over",
+        );
+        check_iter(
+            t,
+            model,
+            t.iter_under(&v),
+            Unbounded,
+            Excluded(v),
+            false,
+            "iter_under",
+        );
+        check_iter(
+            t,
+            model,
+            t.desc_iter_under(&v),
+            Unbounded,
+            Excluded(v),
+            true,
+            "desc_iter_under",
+        );
+
+        for e in [v - 1, v, v + 3] {
+            let (start, end) = (Included(v), Included(e));
+            match t.range(&v, &e) {
+                Some(range) => {
+                    check_found(Some(range.start), model.first(start, end), "range start");
+                    check_found(Some(range.end), model.last(start, end), "range end");
+                }
+                None => assert!(model.range(start, end).is_empty(), "range {v} {e}"),
+            }
+            check_iter(
+                t,
+                model,
+                t.iter_range(&v, &e),
+                start,
+                end,
+                false,
+                "iter_range",
+            );
+            check_iter(
+                t,
+                model,
+                t.desc_iter_range(&v, &e),
+                start,
+                end,
+                true,
+                "desc_iter_range",
+            );
+
+            let distinct: Vec<(i64, u32)> = t
+                .iter_distinct_range(&v, &e)
+                .map(|(head, count)| (*t.value(head).unwrap(), count))
+                .collect();
+            let expected: Vec<(i64, u32)> = model
+                .range(start, end)
+                .into_iter()
+                .map(|(value, rows)| (value, rows.len() as u32))
+                .collect();
+            assert_eq!(distinct, expected, "iter_distinct_range");
+            assert!(
+                t.desc_iter_distinct_range(&v, &e)
+                    .map(|(head, count)| (*t.value(head).unwrap(), count))
+                    .eq(expected.into_iter().rev()),
+                "desc_iter_distinct_range"
+            );
+
+            for offset in [0, 1, 3] {
+                assert!(t
+                    .iter_range_from_offset(&v, &e, offset)
+                    .eq(t.iter_range(&v, &e).skip(offset as usize)));
+                assert!(t
+                    .desc_iter_range_from_offset(&v, &e, offset)
+                    .eq(t.desc_iter_range(&v, &e).skip(offset as usize)));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_model() {
+    for seed in 0..SEEDS {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut t = Avltriee::new();
+        let mut model = Model::default();
+
+        for _ in 0..STEPS {
+            match rng.gen_range(0..12) {
+                0..=1 => {
+                    if t.rows_count() < MAX_ROW {
+                        let value = rng.gen_range(0..=MAX_VALUE);
+                        let r = t.insert(&value);
+                        model.update(r.get(), value);
+                    }
+                }
+                2..=3 => {
+                    let r = rng.gen_range(1..=MAX_ROW);
+                    t.delete(row(r));
+                    model.delete(r);
+                }
+                4..=6 => {
+                    // Moves an existing row to the same branch of another existing value.
+                    if let (Some(&r), Some(&value)) = (
+                        model
+                            .values
+                            .keys()
+                            .nth(rng.gen_range(0..=model.values.len())),
+                        model.map.keys().nth(rng.gen_range(0..=model.map.len())),
+                    ) {
+                        t.update(row(r), &value);
+                        model.update(r, value);
+                    }
+                }
+                10 => {
+                    let start = rng.gen_range(0..=MAX_VALUE);
+                    let end = start + rng.gen_range(0..3);
+                    let mut rows = t.delete_range(&start, &end);
+                    rows.sort();
+                    let expected: Vec<u32> = model
+                        .values
+                        .iter()
+                        .filter(|(_, value)| (start..=end).contains(*value))
+                        .map(|(r, _)| *r)
+                        .collect();
+                    assert!(rows.iter().map(|r| r.get()).eq(expected.iter().copied()));
+                    for r in expected {
+                        model.delete(r);
+                    }
+                }
+                11 => {
+                    let value = rng.gen_range(0..=MAX_VALUE + 1);
+                    let mut upper = t.split_off(&value);
+                    t.validate().unwrap();
+                    upper.validate().unwrap();
+                    assert!(t.iter().all(|r| *t.value(r).unwrap() < value));
+                    assert!(upper.iter().all(|r| *upper.value(r).unwrap() >= value));
+                    if rng.gen() {
+                        t.append(&mut upper);
+                    } else {
+                        upper.append(&mut t);
+                        t = upper;
+                    }
+                }
+                _ => {
+                    let r = rng.gen_range(1..=MAX_ROW);
+                    let value = rng.gen_range(0..=MAX_VALUE);
+                    t.update(row(r), &value);
+                    model.update(r, value);
+                }
+            }
+            check(&t, &model);
+        }
+    }
+}
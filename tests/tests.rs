@@ -112,3 +112,1172 @@ fn test_insert_10000() {
 
     println!("OK:{}", 1000000);
 }
+
+#[test]
+fn test_iter_distinct() {
+    use avltriee::Avltriee;
+    use rand::distributions::{Distribution, Uniform};
+    use std::collections::BTreeMap;
+
+    let mut t = Avltriee::new();
+    let mut model: BTreeMap<i64, u32> = BTreeMap::new();
+
+    let mut rng = rand::thread_rng();
+    let die = Uniform::from(TEST_VALUE_RANGE_MIN..=TEST_VALUE_RANGE_MAX);
+
+    for i in 1..=TEST_LENGTH {
+        let num = die.sample(&mut rng);
+        t.update(i.try_into().unwrap(), &num);
+        *model.entry(num).or_default() += 1;
+    }
+
+    let distinct: Vec<(i64, u32)> = t
+        .iter_distinct()
+        .map(|(row, count)| (*t.value(row).unwrap(), count))
+        .collect();
+    assert_eq!(distinct, model.clone().into_iter().collect::<Vec<_>>());
+
+    let desc: Vec<(i64, u32)> = t
+        .desc_iter_distinct()
+        .map(|(row, count)| (*t.value(row).unwrap(), count))
+        .collect();
+    assert_eq!(desc, model.clone().into_iter().rev().collect::<Vec<_>>());
+
+    let range: Vec<(i64, u32)> = t
+        .iter_distinct_range(&10, &20)
+        .map(|(row, count)| (*t.value(row).unwrap(), count))
+        .collect();
+    assert_eq!(
+        range,
+        model
+            .range(10..=20)
+            .map(|(k, v)| (*k, *v))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_iter_from_offset() {
+    use avltriee::Avltriee;
+    use rand::distributions::{Distribution, Uniform};
+
+    let mut t = Avltriee::new();
+
+    let mut rng = rand::thread_rng();
+    let die = Uniform::from(TEST_VALUE_RANGE_MIN..=TEST_VALUE_RANGE_MAX);
+
+    let a = Uniform::from(1..=TEST_LENGTH);
+    for _ in 1..=TEST_LENGTH {
+        let num = die.sample(&mut rng);
+        t.update(a.sample(&mut rng).try_into().unwrap(), &num);
+    }
+    for _ in 1..=TEST_LENGTH / 2 {
+        t.delete(a.sample(&mut rng).try_into().unwrap());
+    }
+
+    for offset in 0..=TEST_LENGTH {
+        assert!(t
+            .iter_from_offset(offset)
+            .eq(t.iter().skip(offset as usize)));
+        assert!(t
+            .desc_iter_from_offset(offset)
+            .eq(t.desc_iter().skip(offset as usize)));
+        assert!(t
+            .iter_range_from_offset(&10, &40, offset)
+            .eq(t.iter_range(&10, &40).skip(offset as usize)));
+        assert!(t
+            .desc_iter_range_from_offset(&10, &40, offset)
+            .eq(t.desc_iter_range(&10, &40).skip(offset as usize)));
+    }
+}
+
+#[test]
+fn test_nearest() {
+    use avltriee::Avltriee;
+    use rand::distributions::{Distribution, Uniform};
+
+    let mut t = Avltriee::new();
+
+    let mut rng = rand::thread_rng();
+    let die = Uniform::from(TEST_VALUE_RANGE_MIN..=TEST_VALUE_RANGE_MAX);
+
+    for i in 1..=TEST_LENGTH {
+        let num = die.sample(&mut rng) * 2;
+        t.update(i.try_into().unwrap(), &num);
+    }
+
+    let distance = |a: &i64, b: &i64| (a - b).abs();
+    for value in TEST_VALUE_RANGE_MIN - 2..=TEST_VALUE_RANGE_MAX * 2 + 2 {
+        let mut expected: Vec<i64> = t
+            .iter()
+            .map(|row| distance(&value, t.value(row).unwrap()))
+            .collect();
+        expected.sort();
+
+        let nearest: Vec<i64> = t
+            .nearest_iter(&value, distance)
+            .map(|row| distance(&value, t.value(row).unwrap()))
+            .collect();
+        assert_eq!(nearest, expected);
+
+        let row = t.nearest(&value, distance).unwrap();
+        assert_eq!(distance(&value, t.value(row).unwrap()), expected[0]);
+
+        assert_eq!(t.k_nearest(&value, 5, distance).count(), 5);
+    }
+}
+
+#[test]
+fn test_rows_combinators() {
+    use avltriee::{rows, Avltriee};
+    use rand::distributions::{Distribution, Uniform};
+    use std::num::NonZeroU32;
+
+    let mut a = Avltriee::new();
+    let mut b = Avltriee::new();
+
+    let mut rng = rand::thread_rng();
+    let die = Uniform::from(TEST_VALUE_RANGE_MIN..=TEST_VALUE_RANGE_MAX);
+
+    for i in 1..=TEST_LENGTH {
+        a.update(i.try_into().unwrap(), &die.sample(&mut rng));
+        b.update(i.try_into().unwrap(), &die.sample(&mut rng));
+    }
+
+    let a_rows: HashSet<NonZeroU32> = a.iter_range(&10, &30).collect();
+    let b_rows: HashSet<NonZeroU32> = b.iter_to(&20).collect();
+
+    let sorted = |set: HashSet<NonZeroU32>| {
+        let mut rows: Vec<NonZeroU32> = set.into_iter().collect();
+        rows.sort();
+        rows
+    };
+
+    assert_eq!(
+        rows::intersection([a.iter_range(&10, &30), b.iter_to(&20)]),
+        sorted(a_rows.intersection(&b_rows).cloned().collect())
+    );
+    assert_eq!(
+        rows::union([a.iter_range(&10, &30), b.iter_to(&20)]),
+        sorted(a_rows.union(&b_rows).cloned().collect())
+    );
+    assert_eq!(
+        rows::difference(a.iter_range(&10, &30), b.iter_to(&20)),
+        sorted(a_rows.difference(&b_rows).cloned().collect())
+    );
+
+    // Sparse rows are sorted without a bitmap.
+    let sparse = |rows: &[u32]| -> Vec<NonZeroU32> {
+        rows.iter().map(|&row| row.try_into().unwrap()).collect()
+    };
+    let (left, right) = (
+        sparse(&[1_000_000, 5, 3, 5]),
+        sparse(&[7, 1_000_000, 3, 4_000_000]),
+    );
+    assert_eq!(
+        rows::intersection([left.clone(), right.clone()]),
+        sparse(&[3, 1_000_000])
+    );
+    assert_eq!(
+        rows::union([left.clone(), right.clone()]),
+        sparse(&[3, 5, 7, 1_000_000, 4_000_000])
+    );
+    assert_eq!(rows::difference(left, right), sparse(&[5]));
+}
+
+#[test]
+fn test_row_bitmap() {
+    use avltriee::{rows::RowBitmap, Avltriee};
+    use rand::distributions::{Distribution, Uniform};
+    use std::num::NonZeroU32;
+
+    let mut a = Avltriee::new();
+    let mut b = Avltriee::new();
+
+    let mut rng = rand::thread_rng();
+    let die = Uniform::from(TEST_VALUE_RANGE_MIN..=TEST_VALUE_RANGE_MAX);
+
+    for i in 1..=TEST_LENGTH {
+        a.update(i.try_into().unwrap(), &die.sample(&mut rng));
+        b.update(i.try_into().unwrap(), &die.sample(&mut rng));
+    }
+
+    let a_rows: HashSet<NonZeroU32> = a.iter_range(&10, &30).collect();
+    let b_rows: HashSet<NonZeroU32> = b.iter_to(&20).collect();
+    let a_bitmap: RowBitmap = a.iter_range(&10, &30).collect();
+    let b_bitmap: RowBitmap = b.iter_to(&20).collect();
+
+    assert_eq!(a_bitmap.len(), a_rows.len());
+    assert!(a_rows.iter().all(|row| a_bitmap.contains(*row)));
+
+    let sorted = |set: HashSet<NonZeroU32>| {
+        let mut rows: Vec<NonZeroU32> = set.into_iter().collect();
+        rows.sort();
+        rows
+    };
+
+    assert_eq!(
+        a_bitmap.and(&b_bitmap).iter().collect::<Vec<_>>(),
+        sorted(a_rows.intersection(&b_rows).cloned().collect())
+    );
+    assert_eq!(
+        a_bitmap.or(&b_bitmap).iter().collect::<Vec<_>>(),
+        sorted(a_rows.union(&b_rows).cloned().collect())
+    );
+    assert_eq!(
+        a_bitmap.not(TEST_LENGTH).iter().collect::<Vec<_>>(),
+        sorted(a.iter().filter(|row| !a_rows.contains(row)).collect())
+    );
+}
+
+#[test]
+fn test_merge_join() {
+    use avltriee::Avltriee;
+    use rand::distributions::{Distribution, Uniform};
+    use std::collections::BTreeMap;
+
+    let mut left = Avltriee::new();
+    let mut right = Avltriee::new();
+    let mut left_model: BTreeMap<i64, HashSet<u32>> = BTreeMap::new();
+    let mut right_model: BTreeMap<i64, HashSet<u32>> = BTreeMap::new();
+
+    let mut rng = rand::thread_rng();
+    let die = Uniform::from(TEST_VALUE_RANGE_MIN..=TEST_VALUE_RANGE_MAX);
+
+    for i in 1..=TEST_LENGTH {
+        let num = die.sample(&mut rng);
+        left.update(i.try_into().unwrap(), &num);
+        left_model.entry(num).or_default().insert(i);
+    }
+    for i in 1..=TEST_LENGTH / 2 {
+        let num = die.sample(&mut rng);
+        right.update(i.try_into().unwrap(), &num);
+        right_model.entry(num).or_default().insert(i);
+    }
+
+    let joined: Vec<(i64, HashSet<u32>, HashSet<u32>)> = left
+        .merge_join(&right)
+        .map(|(l, r)| {
+            let l: HashSet<u32> = l.map(|row| row.get()).collect();
+            let r: HashSet<u32> = r.map(|row| row.get()).collect();
+            let value = *left
+                .value((*l.iter().next().unwrap()).try_into().unwrap())
+                .unwrap();
+            (value, l, r)
+        })
+        .collect();
+    let expected: Vec<(i64, HashSet<u32>, HashSet<u32>)> = left_model
+        .iter()
+        .filter_map(|(value, l)| {
+            right_model
+                .get(value)
+                .map(|r| (*value, l.clone(), r.clone()))
+        })
+        .collect();
+    assert_eq!(joined, expected);
+}
+
+#[test]
+fn test_diff() {
+    use avltriee::{Avltriee, AvltrieeDiff};
+
+    let mut left = Avltriee::new();
+    let mut right = Avltriee::new();
+
+    for i in 1..=10u32 {
+        left.update(i.try_into().unwrap(), &(i as i64));
+        right.update(i.try_into().unwrap(), &(i as i64));
+    }
+    right.update(3.try_into().unwrap(), &100);
+    right.delete(5.try_into().unwrap());
+    right.update(12.try_into().unwrap(), &7);
+    left.delete(8.try_into().unwrap());
+
+    assert_eq!(
+        left.diff(&right).collect::<Vec<_>>(),
+        vec![
+            AvltrieeDiff::Changed(3.try_into().unwrap()),
+            AvltrieeDiff::Removed(5.try_into().unwrap()),
+            AvltrieeDiff::Added(8.try_into().unwrap()),
+            AvltrieeDiff::Added(12.try_into().unwrap()),
+        ]
+    );
+}
+
+/// Allocator whose nodes stay reachable after it is moved into a triee, so that tests can corrupt them.
+struct SharedAllocator(std::rc::Rc<std::cell::UnsafeCell<avltriee::VecAvltrieeAllocator<i64>>>);
+
+impl avltriee::AvltrieeAllocator<i64> for SharedAllocator {
+    type Layout = avltriee::WideAvltrieeLayout;
+    type NodeValue = i64;
+
+    unsafe fn node_ptr(&self, index: usize) -> *const avltriee::AvltrieeNode<i64> {
+        (*self.0.get()).node_ptr(index)
+    }
+
+    unsafe fn node_mut_ptr(&mut self, index: usize) -> *mut avltriee::AvltrieeNode<i64> {
+        (*self.0.get()).node_mut_ptr(index)
+    }
+
+    fn get(&self, row: std::num::NonZeroU32) -> Option<&avltriee::AvltrieeNode<i64>> {
+        unsafe { (*self.0.get()).get(row) }
+    }
+
+    unsafe fn value_ptr(&self, index: usize) -> *const i64 {
+        (*self.0.get()).value_ptr(index)
+    }
+
+    unsafe fn value_mut_ptr(&mut self, index: usize) -> *mut i64 {
+        (*self.0.get()).value_mut_ptr(index)
+    }
+
+    fn resize(&mut self, rows_count: usize) {
+        unsafe { (*self.0.get()).resize(rows_count) }
+    }
+
+    fn allocated_bytes(&self) -> usize {
+        unsafe { (*self.0.get()).allocated_bytes() }
+    }
+
+    fn same_table(&self) -> &() {
+        &()
+    }
+
+    fn same_table_mut(&mut self) -> &mut () {
+        unsafe { (*self.0.get()).same_table_mut() }
+    }
+}
+
+/// Byte offsets of the fields of a node with NonZeroU32 rows, which is laid out in declaration order.
+/// The parent link of row 0 holds the root.
+const PARENT: usize = 0;
+const LEFT: usize = 4;
+const RIGHT: usize = 8;
+const HEIGHT: usize = 12;
+const SAME: usize = 20;
+
+fn field<V>(allocator: &SharedAllocator, row: u32, offset: usize) -> *mut V {
+    use avltriee::AvltrieeAllocator;
+
+    unsafe {
+        let node = (*allocator.0.get()).node_mut_ptr(row as usize) as *mut u8;
+        node.add(offset).cast()
+    }
+}
+
+/// Overwrites the field at the specified byte offset of the node of the row, bypassing the triee.
+fn corrupt<V>(allocator: &SharedAllocator, row: u32, offset: usize, value: V) {
+    unsafe { field::<V>(allocator, row, offset).write(value) }
+}
+
+/// Returns the link at the specified byte offset of the node of the row. 0 if None.
+fn link(allocator: &SharedAllocator, row: u32, offset: usize) -> u32 {
+    unsafe { field::<u32>(allocator, row, offset).read() }
+}
+
+#[test]
+fn test_wal_replay() {
+    use avltriee::{Avltriee, AvltrieeWal, AvltrieeWalSync};
+    use std::io::Write;
+
+    let path = std::env::temp_dir().join(format!("avltriee_test_wal_{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut t = Avltriee::new();
+    {
+        let mut wal = AvltrieeWal::open(&path, AvltrieeWalSync::Every(10)).unwrap();
+        for i in 1..=TEST_LENGTH {
+            wal.insert(&mut t, &((i % 7) as i64)).unwrap();
+        }
+        wal.update(&mut t, 3.try_into().unwrap(), &100).unwrap();
+        wal.delete(&mut t, 5.try_into().unwrap()).unwrap();
+        wal.sync().unwrap();
+    }
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap()
+        .write_all(&[1, 9, 0])
+        .unwrap();
+
+    let mut replayed = Avltriee::new();
+    AvltrieeWal::open(&path, AvltrieeWalSync::Always)
+        .unwrap()
+        .replay(&mut replayed)
+        .unwrap();
+    assert_eq!(replayed.diff(&t).count(), 0);
+    assert!(t.iter().eq(replayed.iter()));
+
+    let mut wal = AvltrieeWal::open(&path, AvltrieeWalSync::Always).unwrap();
+    wal.delete(&mut t, 6.try_into().unwrap()).unwrap();
+    let intact = std::fs::metadata(&path).unwrap().len();
+    for tail in [
+        &[7, 1, 2, 3][..],
+        &[1, 2, 0, 0, 0, 0, 0, 0, 0, 255, 255, 255, 255],
+    ] {
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(tail)
+            .unwrap();
+        let mut replayed = Avltriee::new();
+        wal.replay(&mut replayed).unwrap();
+        assert_eq!(replayed.diff(&t).count(), 0);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), intact);
+    }
+
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes[intact as usize - 1] ^= 1;
+    std::fs::write(&path, &bytes).unwrap();
+    let mut replayed = Avltriee::<i64>::new();
+    wal.replay(&mut replayed).unwrap();
+    assert_eq!(replayed.value(6.try_into().unwrap()), Some(&6));
+    assert!(std::fs::metadata(&path).unwrap().len() < intact);
+
+    wal.checkpoint().unwrap();
+    let mut empty = Avltriee::<i64>::new();
+    wal.replay(&mut empty).unwrap();
+    assert_eq!(empty.iter().count(), 0);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_wal_replay_corrupted() {
+    use avltriee::{
+        Avltriee, AvltrieeAllocator, AvltrieeWal, AvltrieeWalSync, VecAvltrieeAllocator,
+    };
+
+    let path = std::env::temp_dir().join(format!(
+        "avltriee_test_wal_corrupted_{}",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+
+    let allocator = SharedAllocator(Default::default());
+    let mut t = Avltriee::with_allocator(SharedAllocator(allocator.0.clone()));
+    let mut expected: Avltriee<i64, i64, VecAvltrieeAllocator<i64>> = Avltriee::new();
+    let mut wal = AvltrieeWal::open(&path, AvltrieeWalSync::Manual).unwrap();
+    for i in 1..=TEST_LENGTH {
+        wal.insert(&mut t, &((i % 7) as i64)).unwrap();
+        expected.update(i.try_into().unwrap(), &((i % 7) as i64));
+    }
+    wal.update(&mut t, 3.try_into().unwrap(), &100).unwrap();
+    expected.update(3.try_into().unwrap(), &100);
+    wal.delete(&mut t, 5.try_into().unwrap()).unwrap();
+    expected.delete(5.try_into().unwrap());
+    wal.sync().unwrap();
+
+    // Links left half written by a crash in the middle of an operation.
+    corrupt(&allocator, 1, PARENT, 0u32);
+    corrupt(&allocator, 2, LEFT, 4u32);
+    corrupt(&allocator, 3, RIGHT, 3u32);
+    assert!(t.validate().is_err());
+
+    wal.replay(&mut t).unwrap();
+    t.validate().unwrap();
+    for row in 1..=TEST_LENGTH {
+        let row = row.try_into().unwrap();
+        assert_eq!(t.value(row), expected.value(row));
+    }
+    assert!(t
+        .iter()
+        .map(|row| t.value(row))
+        .eq(expected.iter().map(|row| expected.value(row))));
+
+    // A value left out of order by a crash after the checkpoint, with intact links.
+    wal.checkpoint().unwrap();
+    let root = link(&allocator, 0, PARENT);
+    let left = link(&allocator, root, LEFT);
+    unsafe { *(*allocator.0.get()).value_mut_ptr(left as usize) = 1000 };
+    wal.replay(&mut t).unwrap();
+    t.validate().unwrap();
+    assert_eq!(t.row(&1000), Some(left.try_into().unwrap()));
+    assert!(t.iter().map(|row| t.value(row).unwrap()).is_sorted());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_wal_write_failure() {
+    use avltriee::{Avltriee, AvltrieeWal, AvltrieeWalSync};
+
+    // Every write to /dev/full fails with ENOSPC, and it cannot be truncated.
+    let mut wal = AvltrieeWal::open("/dev/full", AvltrieeWalSync::Manual).unwrap();
+    let mut t = Avltriee::<i64>::new();
+    assert!(wal.insert(&mut t, &1).is_err());
+    assert_eq!(t.iter().count(), 0);
+
+    // The log cannot be undone, so later operations must not be acknowledged.
+    t.insert(&2);
+    assert!(wal.update(&mut t, 1.try_into().unwrap(), &3).is_err());
+    assert!(wal.delete(&mut t, 1.try_into().unwrap()).is_err());
+    assert_eq!(t.value(1.try_into().unwrap()), Some(&2));
+}
+
+#[test]
+fn test_validate_rebuild() {
+    use avltriee::Avltriee;
+    use rand::distributions::{Distribution, Uniform};
+
+    let mut rng = rand::thread_rng();
+    let die = Uniform::from(TEST_VALUE_RANGE_MIN..=TEST_VALUE_RANGE_MAX);
+    let a = Uniform::from(1..=TEST_LENGTH);
+
+    // Each corruption is applied to a fresh triee.
+    for corruption in ["left", "right", "parent", "same", "same loop", "height"] {
+        let allocator = SharedAllocator(Default::default());
+        let mut t = Avltriee::with_allocator(SharedAllocator(allocator.0.clone()));
+        for _ in 1..=TEST_LENGTH * 10 {
+            let row = a.sample(&mut rng).try_into().unwrap();
+            if die.sample(&mut rng) < 15 {
+                t.delete(row);
+            } else {
+                t.update(row, &die.sample(&mut rng));
+            }
+        }
+        t.validate().unwrap();
+
+        let before: Vec<(i64, u32)> = t
+            .iter_distinct()
+            .map(|(row, count)| (*t.value(row).unwrap(), count))
+            .collect();
+        let mut rows: Vec<(i64, u32)> = t
+            .iter()
+            .map(|row| (*t.value(row).unwrap(), row.get()))
+            .collect();
+        rows.sort();
+
+        let root = link(&allocator, 0, PARENT);
+        let chained = (1..=TEST_LENGTH)
+            .find(|&row| link(&allocator, row, SAME) != 0)
+            .unwrap();
+        match corruption {
+            "left" => corrupt(&allocator, root, LEFT, 0u32),
+            "right" => corrupt(&allocator, root, RIGHT, root),
+            "parent" => corrupt(&allocator, link(&allocator, root, LEFT), PARENT, 0u32),
+            "same" => corrupt(&allocator, chained, SAME, 0u32),
+            "same loop" => corrupt(&allocator, chained, SAME, chained),
+            _ => corrupt(&allocator, root, HEIGHT, 1u8),
+        }
+        assert!(t.validate().is_err(), "{corruption}");
+
+        t.rebuild();
+        t.validate().unwrap();
+        let after: Vec<(i64, u32)> = t
+            .iter_distinct()
+            .map(|(row, count)| (*t.value(row).unwrap(), count))
+            .collect();
+        assert_eq!(before, after);
+        let mut rebuilt: Vec<(i64, u32)> = t
+            .iter()
+            .map(|row| (*t.value(row).unwrap(), row.get()))
+            .collect();
+        rebuilt.sort();
+        assert_eq!(rows, rebuilt);
+        let len = rows.len() as u32;
+        assert!(t
+            .iter_from_offset(len / 2)
+            .eq(t.iter().skip(len as usize / 2)));
+    }
+}
+
+#[test]
+fn test_validate_value_order() {
+    use avltriee::{Avltriee, AvltrieeAllocator};
+
+    let allocator = SharedAllocator(Default::default());
+    let mut t = Avltriee::with_allocator(SharedAllocator(allocator.0.clone()));
+    for value in [10, 5, 15, 3, 7] {
+        t.insert(&value);
+    }
+    t.validate().unwrap();
+
+    // 12 is greater than its parent 5 but not less than its grandparent 10.
+    unsafe { *(*allocator.0.get()).value_mut_ptr(5) = 12 };
+    assert!(t.validate().is_err());
+
+    t.rebuild();
+    t.validate().unwrap();
+    assert!(t
+        .iter()
+        .map(|row| *t.value(row).unwrap())
+        .eq([3, 5, 10, 12, 15]));
+    assert_eq!(t.row(&12), Some(5.try_into().unwrap()));
+}
+
+#[test]
+fn test_chunked_allocator() {
+    use avltriee::{Avltriee, AvltrieeAllocator, ChunkedAvltrieeAllocator};
+    use rand::distributions::{Distribution, Uniform};
+
+    let mut t: Avltriee<i64, i64, ChunkedAvltrieeAllocator<i64, 8>> =
+        Avltriee::with_allocator(ChunkedAvltrieeAllocator::new());
+
+    let mut rng = rand::thread_rng();
+    let die = Uniform::from(TEST_VALUE_RANGE_MIN..=TEST_VALUE_RANGE_MAX);
+
+    t.update(1.try_into().unwrap(), &die.sample(&mut rng));
+    let first = t.node(1.try_into().unwrap()).unwrap() as *const _;
+
+    let a = Uniform::from(1..=TEST_LENGTH);
+    for _ in 1..=TEST_LENGTH * 10 {
+        let row = a.sample(&mut rng).try_into().unwrap();
+        if die.sample(&mut rng) < 15 {
+            t.delete(row);
+        } else {
+            t.update(row, &die.sample(&mut rng));
+        }
+        t.validate().unwrap();
+    }
+    t.update(1.try_into().unwrap(), &die.sample(&mut rng));
+    assert_eq!(t.node(1.try_into().unwrap()).unwrap() as *const _, first);
+
+    let mut expected: Vec<i64> = t.iter().map(|row| *t.value(row).unwrap()).collect();
+    expected.sort();
+    assert_eq!(
+        t.iter()
+            .map(|row| *t.value(row).unwrap())
+            .collect::<Vec<_>>(),
+        expected
+    );
+
+    // Growing after a shrink clears the slots left in the kept chunk and adds default chunks.
+    let mut allocator: ChunkedAvltrieeAllocator<i64, 4> = ChunkedAvltrieeAllocator::new();
+    allocator.resize(10);
+    for index in 1..=10 {
+        unsafe { *allocator.value_mut_ptr(index) = index as i64 };
+    }
+    allocator.resize(2);
+    allocator.resize(10);
+    for index in 1..=10 {
+        let expected = if index <= 2 { index as i64 } else { 0 };
+        assert_eq!(unsafe { *allocator.value_ptr(index) }, expected);
+    }
+}
+
+#[test]
+fn test_row_u16() {
+    use std::num::NonZeroU16;
+
+    use avltriee::{Avltriee, VecAvltrieeAllocator};
+    use rand::distributions::{Distribution, Uniform};
+
+    let mut t: Avltriee<i64, i64, VecAvltrieeAllocator<i64, NonZeroU16>, NonZeroU16> =
+        Avltriee::with_allocator(VecAvltrieeAllocator::new());
+
+    let mut rng = rand::thread_rng();
+    let die = Uniform::from(TEST_VALUE_RANGE_MIN..=TEST_VALUE_RANGE_MAX);
+
+    let a = Uniform::from(1..=TEST_LENGTH as u16);
+    for _ in 1..=TEST_LENGTH * 10 {
+        let row = NonZeroU16::new(a.sample(&mut rng)).unwrap();
+        if die.sample(&mut rng) < 15 {
+            t.delete(row);
+        } else {
+            t.update(row, &die.sample(&mut rng));
+        }
+        t.validate().unwrap();
+    }
+
+    let live: Vec<NonZeroU16> = (1..=t.rows_count())
+        .filter_map(NonZeroU16::new)
+        .filter(|&row| t.node(row).is_some())
+        .collect();
+    assert_eq!(t.iter().count(), live.len());
+    assert_eq!(
+        t.iter_from_offset(live.len() as u16 / 2).count(),
+        live.len() - live.len() / 2
+    );
+}
+
+#[test]
+#[should_panic(expected = "row overflow")]
+fn test_insert_row_u16_full() {
+    use std::num::NonZeroU16;
+
+    use avltriee::{Avltriee, AvltrieeUpdate, VecAvltrieeAllocator};
+
+    let mut t: Avltriee<i64, i64, VecAvltrieeAllocator<i64, NonZeroU16>, NonZeroU16> =
+        Avltriee::with_allocator(VecAvltrieeAllocator::new());
+
+    for i in 1..=u16::MAX {
+        assert_eq!(t.insert(&(i as i64 % 100)).get(), i);
+    }
+    assert_eq!(t.rows_count(), u16::MAX);
+    assert_eq!(t.len(), u16::MAX);
+    t.validate().unwrap();
+
+    t.insert(&0);
+}
+
+#[test]
+fn test_packed_layout() {
+    use std::num::NonZeroU32;
+
+    use avltriee::{
+        Avltriee, AvltrieeNode, PackedAvltrieeLayout, VecAvltrieeAllocator, WideAvltrieeLayout,
+    };
+    use rand::distributions::{Distribution, Uniform};
+
+    assert_eq!(
+        std::mem::size_of::<AvltrieeNode<u32, NonZeroU32, WideAvltrieeLayout>>(),
+        28
+    );
+    assert_eq!(
+        std::mem::size_of::<AvltrieeNode<u32, NonZeroU32, PackedAvltrieeLayout>>(),
+        20
+    );
+
+    let mut t: Avltriee<i64, i64, VecAvltrieeAllocator<i64, NonZeroU32, PackedAvltrieeLayout>> =
+        Avltriee::with_allocator(VecAvltrieeAllocator::new());
+
+    let mut rng = rand::thread_rng();
+    let die = Uniform::from(TEST_VALUE_RANGE_MIN..=TEST_VALUE_RANGE_MAX);
+
+    let a = Uniform::from(1..=TEST_LENGTH);
+    for _ in 1..=TEST_LENGTH * 10 {
+        let row = a.sample(&mut rng).try_into().unwrap();
+        if die.sample(&mut rng) < 15 {
+            t.delete(row);
+        } else {
+            t.update(row, &die.sample(&mut rng));
+        }
+        t.validate().unwrap();
+    }
+
+    let live = t.iter().count() as u32;
+    assert_eq!(t.iter_from_offset(live / 2).count() as u32, live - live / 2);
+
+    // Same links of duplicates are kept in the table, which is counted as well.
+    let allocated_bytes = |value: fn(u32) -> i64| {
+        let mut t: Avltriee<i64, i64, VecAvltrieeAllocator<i64, NonZeroU32, PackedAvltrieeLayout>> =
+            Avltriee::with_allocator(VecAvltrieeAllocator::new());
+        for row in 1..=TEST_LENGTH {
+            t.update(row.try_into().unwrap(), &value(row));
+        }
+        t.stats().allocated_bytes
+    };
+    let links = (TEST_LENGTH as usize - 1) * 2 * std::mem::size_of::<u32>();
+    assert!(allocated_bytes(|_| 0) >= allocated_bytes(|row| row as i64) + links);
+}
+
+#[test]
+fn test_split_allocator() {
+    use avltriee::{Avltriee, SplitAvltrieeAllocator};
+    use rand::distributions::{Distribution, Uniform};
+
+    let mut t: Avltriee<i64, i64, SplitAvltrieeAllocator<i64>> =
+        Avltriee::with_allocator(SplitAvltrieeAllocator::new());
+
+    let mut rng = rand::thread_rng();
+    let die = Uniform::from(TEST_VALUE_RANGE_MIN..=TEST_VALUE_RANGE_MAX);
+
+    let a = Uniform::from(1..=TEST_LENGTH);
+    for _ in 1..=TEST_LENGTH * 10 {
+        let row = a.sample(&mut rng).try_into().unwrap();
+        if die.sample(&mut rng) < 15 {
+            t.delete(row);
+        } else {
+            t.update(row, &die.sample(&mut rng));
+        }
+        t.validate().unwrap();
+    }
+
+    let values: Vec<i64> = t.iter().map(|row| *t.value(row).unwrap()).collect();
+    let mut expected = values.clone();
+    expected.sort();
+    assert_eq!(values, expected);
+    for value in TEST_VALUE_RANGE_MIN..=TEST_VALUE_RANGE_MAX {
+        for row in t.iter_by(&value) {
+            assert_eq!(*t.value(row).unwrap(), value);
+        }
+    }
+}
+
+#[test]
+fn test_freeze() {
+    use avltriee::Avltriee;
+    use rand::distributions::{Distribution, Uniform};
+
+    let mut t = Avltriee::new();
+
+    let mut rng = rand::thread_rng();
+    let die = Uniform::from(TEST_VALUE_RANGE_MIN..=TEST_VALUE_RANGE_MAX);
+
+    let a = Uniform::from(1..=TEST_LENGTH);
+    for _ in 1..=TEST_LENGTH * 2 {
+        let row = a.sample(&mut rng).try_into().unwrap();
+        if die.sample(&mut rng) < 15 {
+            t.delete(row);
+        } else {
+            t.update(row, &die.sample(&mut rng));
+        }
+    }
+
+    let f = t.freeze();
+    assert_eq!(f.len(), t.iter().count());
+    assert!(f.iter().eq(t.iter()));
+    assert!(f.desc_iter().eq(t.desc_iter()));
+    for row in 1..=TEST_LENGTH {
+        let row = row.try_into().unwrap();
+        assert_eq!(f.value(row), t.value(row));
+    }
+    for v in TEST_VALUE_RANGE_MIN - 1..=TEST_VALUE_RANGE_MAX + 1 {
+        assert_eq!(f.row(&v), t.row(&v));
+        assert_eq!(f.ge(&v), t.ge(&v));
+        assert_eq!(f.le(&v), t.le(&v));
+        assert_eq!(f.gt(&v), t.gt(&v));
+        assert_eq!(f.lt(&v), t.lt(&v));
+        assert_eq!(f.range(&v, &(v + 5)), t.range(&v, &(v + 5)));
+        assert!(f.iter_by(&v).eq(t.iter_by(&v)));
+        assert!(f.iter_from(&v).eq(t.iter_from(&v)));
+        assert!(f.desc_iter_from(&v).eq(t.desc_iter_from(&v)));
+        assert!(f.iter_to(&v).eq(t.iter_to(&v)));
+        assert!(f.desc_iter_to(&v).eq(t.desc_iter_to(&v)));
+        assert!(f.iter_over(&v).eq(t.iter_over(&v)));
+        assert!(f.desc_iter_over(&v).eq(t.desc_iter_over(&v)));
+        assert!(f.iter_under(&v).eq(t.iter_under(&v)));
+        assert!(f.desc_iter_under(&v).eq(t.desc_iter_under(&v)));
+        assert!(f.iter_range(&v, &(v + 5)).eq(t.iter_range(&v, &(v + 5))));
+        assert!(f
+            .desc_iter_range(&v, &(v + 5))
+            .eq(t.desc_iter_range(&v, &(v + 5))));
+    }
+}
+
+#[test]
+fn test_update_lower_row() {
+    use avltriee::Avltriee;
+
+    let mut t = Avltriee::new();
+    t.update(5.try_into().unwrap(), &50);
+    t.update(2.try_into().unwrap(), &20);
+    t.update(3.try_into().unwrap(), &50);
+
+    assert_eq!(t.rows_count(), 5);
+    assert_eq!(t.value(5.try_into().unwrap()), Some(&50));
+    assert_eq!(
+        t.iter().map(|row| row.get()).collect::<Vec<_>>(),
+        vec![2, 3, 5]
+    );
+}
+
+#[test]
+fn test_delete_long_same_branch() {
+    use avltriee::Avltriee;
+    use rand::seq::SliceRandom;
+
+    let mut t = Avltriee::new();
+    for i in 1..=TEST_LENGTH {
+        t.update(i.try_into().unwrap(), &((i % 3) as i64));
+    }
+
+    let mut rows: Vec<u32> = (1..=TEST_LENGTH).collect();
+    rows.shuffle(&mut rand::thread_rng());
+    for (i, &row) in rows.iter().enumerate() {
+        t.delete(row.try_into().unwrap());
+        assert_eq!(t.iter().count(), rows.len() - i - 1);
+    }
+}
+
+#[test]
+fn test_dump() {
+    use avltriee::Avltriee;
+
+    let mut t = Avltriee::new();
+    for (i, value) in [20, 10, 30, 10, 10].iter().enumerate() {
+        t.update((i as u32 + 1).try_into().unwrap(), value);
+    }
+
+    assert_eq!(
+        t.to_ascii(|value: &i32| value.to_string()),
+        "1: 20 (h=2 bf=0)\n├─L 5: 10 (h=1 bf=0)\n│  └─S 4, 2\n└─R 3: 30 (h=1 bf=0)\n"
+    );
+
+    let dot = t.to_dot(|value: &i32| format!("\"{value}\""));
+    assert!(dot.starts_with("digraph avltriee {"));
+    assert!(dot.contains("1 [label=\"1: \\\"20\\\"\\nh=2 bf=0\"];"));
+    assert!(dot.contains("1 -> 5 [label=\"L\"];"));
+    assert!(dot.contains("5 -> 4 [style=dashed];"));
+    assert!(dot.contains("4 -> 2 [style=dashed];"));
+}
+
+#[test]
+fn test_stats() {
+    use avltriee::{Avltriee, AvltrieeStats};
+
+    let mut t = Avltriee::new();
+    assert_eq!(t.stats().rows, 0);
+
+    for i in 1..=100u32 {
+        t.update(i.try_into().unwrap(), &(i % 10));
+    }
+    t.update(110.try_into().unwrap(), &100);
+    for i in 1..=5u32 {
+        t.delete((i * 10).try_into().unwrap());
+    }
+
+    let stats = t.stats();
+    assert_eq!(
+        stats,
+        AvltrieeStats {
+            rows: 96,
+            slots: 110,
+            holes: 14,
+            distinct: 11,
+            max_same: 10,
+            avg_same: 96.0 / 11.0,
+            height: stats.height,
+            max_height: 4,
+            allocated_bytes: stats.allocated_bytes,
+        }
+    );
+    assert!(stats.height <= stats.max_height);
+    assert!(stats.allocated_bytes >= 111 * std::mem::size_of::<avltriee::AvltrieeNode<u32>>());
+}
+
+#[test]
+fn test_len() {
+    use avltriee::Avltriee;
+
+    let mut t = Avltriee::new();
+    assert!(t.is_empty());
+
+    t.update(10.try_into().unwrap(), &1);
+    t.update(3.try_into().unwrap(), &1);
+    t.update(5.try_into().unwrap(), &2);
+    assert_eq!(t.len(), 3);
+    assert_eq!(t.rows_count(), 10);
+
+    t.update(3.try_into().unwrap(), &2);
+    t.update(3.try_into().unwrap(), &2);
+    assert_eq!(t.len(), 3);
+
+    t.delete(5.try_into().unwrap());
+    t.delete(5.try_into().unwrap());
+    assert_eq!(t.len(), 2);
+
+    t.rebuild();
+    assert_eq!(t.len(), 2);
+
+    t.delete(10.try_into().unwrap());
+    t.delete(3.try_into().unwrap());
+    assert!(t.is_empty());
+    t.validate().unwrap();
+}
+
+#[test]
+fn test_histogram() {
+    use avltriee::Avltriee;
+
+    let mut t = Avltriee::new();
+    assert_eq!(t.estimate_range_count(&0, &100), 0);
+    assert!(t.histogram(4).is_empty());
+
+    for i in 1..=TEST_LENGTH {
+        t.update(i.try_into().unwrap(), &((i % 100) as i64));
+    }
+    for (start, end) in [(0, 99), (10, 20), (20, 10), (50, 50), (-5, 3), (98, 200)] {
+        assert_eq!(
+            t.estimate_range_count(&start, &end) as usize,
+            t.iter_range(&start, &end).count()
+        );
+    }
+
+    let buckets = t.histogram(7);
+    assert_eq!(buckets.len(), 7);
+    assert_eq!(buckets.iter().map(|b| b.count).sum::<u32>(), TEST_LENGTH);
+    let mut iter = t.iter();
+    for bucket in &buckets {
+        let rows: Vec<_> = iter.by_ref().take(bucket.count as usize).collect();
+        assert_eq!(rows.first(), Some(&bucket.start));
+        assert_eq!(rows.last(), Some(&bucket.end));
+    }
+    assert!(buckets
+        .windows(2)
+        .all(|b| b[0].count.abs_diff(b[1].count) <= 1));
+
+    assert_eq!(
+        t.histogram(TEST_LENGTH as usize * 2).len(),
+        TEST_LENGTH as usize
+    );
+}
+
+#[test]
+fn test_quantile() {
+    use avltriee::Avltriee;
+
+    let mut t = Avltriee::new();
+    assert_eq!(t.quantile(0.5), None);
+
+    for (i, value) in [5, 1, 3, 3, 9, 3, 7, 1].iter().enumerate() {
+        t.update((i as u32 + 1).try_into().unwrap(), value);
+    }
+    let rows: Vec<_> = t.iter().collect();
+
+    assert_eq!(t.quantile(0.0), Some(rows[0]));
+    assert_eq!(t.quantile(0.5), Some(rows[3]));
+    assert_eq!(t.quantile(1.0), Some(rows[7]));
+    assert_eq!(t.quantile(1.5), None);
+    assert_eq!(t.quantile(f64::NAN), None);
+    assert_eq!(
+        t.quantiles(&[0.25, 0.5, 0.75])
+            .into_iter()
+            .map(|row| *t.value(row.unwrap()).unwrap())
+            .collect::<Vec<_>>(),
+        vec![1, 3, 5]
+    );
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn test_sample() {
+    use avltriee::Avltriee;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut t = Avltriee::new();
+    assert_eq!(t.sample(&mut rng), None);
+
+    // Value 0 has 9 rows and value 1 has 1 row.
+    for i in 1..=10u32 {
+        t.update(i.try_into().unwrap(), &u32::from(i == 10));
+    }
+    let mut hits = [0; 11];
+    for _ in 0..10000 {
+        hits[t.sample(&mut rng).unwrap().get() as usize] += 1;
+    }
+    assert!(hits[1..].iter().all(|&hit| (800..1200).contains(&hit)));
+
+    assert_eq!(
+        t.sample_range(&1, &5, &mut rng),
+        Some(10.try_into().unwrap())
+    );
+    assert_eq!(t.sample_range(&2, &5, &mut rng), None);
+    for _ in 0..100 {
+        assert_eq!(t.value(t.sample_range(&0, &0, &mut rng).unwrap()), Some(&0));
+    }
+}
+
+#[test]
+fn test_delete_range() {
+    use avltriee::Avltriee;
+
+    let mut t = Avltriee::new();
+    for i in 1..=TEST_LENGTH {
+        t.update(i.try_into().unwrap(), &((i % 100) as i64));
+    }
+
+    let expected: Vec<_> = t.iter_range(&10, &19).collect();
+    assert_eq!(t.delete_range(&10, &19), expected);
+    t.validate().unwrap();
+    assert_eq!(t.iter_range(&10, &19).count(), 0);
+    assert_eq!(t.len(), TEST_LENGTH - expected.len() as u32);
+
+    let expected: Vec<_> = t.iter_under(&70).collect();
+    assert_eq!(t.delete_under(&70), expected);
+    t.validate().unwrap();
+    assert!(t.iter().all(|row| *t.value(row).unwrap() >= 70));
+    assert_eq!(t.iter().count(), t.len() as usize);
+
+    assert!(t.delete_under(&0).is_empty());
+    t.validate().unwrap();
+}
+
+#[test]
+fn test_delete_range_rows_count() {
+    use avltriee::Avltriee;
+
+    // The first range deletes rows one by one, the second crosses half of the rows and rebuilds.
+    for start in [TEST_LENGTH - 10, TEST_LENGTH / 4] {
+        let mut t = Avltriee::new();
+        let mut per_row = Avltriee::new();
+        for i in 1..=TEST_LENGTH {
+            t.update(i.try_into().unwrap(), &(i as i64));
+            per_row.update(i.try_into().unwrap(), &(i as i64));
+        }
+
+        let deleted = t.delete_range(&(start as i64), &(TEST_LENGTH as i64));
+        for &row in &deleted {
+            per_row.delete(row);
+        }
+        t.validate().unwrap();
+        assert_eq!(t.rows_count(), per_row.rows_count());
+        assert_eq!(t.len(), per_row.len());
+
+        let row = t.insert(&0);
+        assert_eq!(row.get(), per_row.rows_count() + 1);
+        t.validate().unwrap();
+        assert_eq!(t.len(), per_row.len() + 1);
+        assert_eq!(t.iter().next(), Some(row));
+    }
+}
+
+#[test]
+fn test_split_off_append() {
+    use avltriee::Avltriee;
+
+    let mut t = Avltriee::new();
+    for i in 1..=TEST_LENGTH {
+        t.update(i.try_into().unwrap(), &((i % 100) as i64));
+    }
+    let all: Vec<_> = t.iter().collect();
+
+    for value in [-1, 0, 37, 50, 99, 100] {
+        let expected_lower: Vec<_> = t.iter_under(&value).collect();
+        let expected_upper: Vec<_> = t.iter_from(&value).collect();
+
+        let mut upper = t.split_off(&value);
+        t.validate().unwrap();
+        upper.validate().unwrap();
+        assert_eq!(t.iter().collect::<Vec<_>>(), expected_lower);
+        assert_eq!(upper.iter().collect::<Vec<_>>(), expected_upper);
+        assert_eq!(t.len() as usize, expected_lower.len());
+        assert_eq!(upper.len() as usize, expected_upper.len());
+        let highest = |rows: &[std::num::NonZeroU32]| rows.iter().map(|row| row.get()).max();
+        assert_eq!(t.rows_count(), TEST_LENGTH);
+        assert_eq!(upper.rows_count(), highest(&expected_upper).unwrap_or(0));
+
+        if value % 2 == 0 {
+            t.append(&mut upper);
+        } else {
+            upper.append(&mut t);
+            std::mem::swap(&mut t, &mut upper);
+        }
+        t.validate().unwrap();
+        upper.validate().unwrap();
+        assert!(upper.is_empty());
+        assert_eq!(t.rows_count(), TEST_LENGTH);
+        assert_eq!(t.iter().collect::<Vec<_>>(), all);
+    }
+
+    let mut upper = t.split_off(&50);
+    upper.update((TEST_LENGTH + 1).try_into().unwrap(), &200);
+    t.append(&mut upper);
+    t.validate().unwrap();
+    assert_eq!(t.len(), TEST_LENGTH + 1);
+
+    // Rows inserted after the split must not collide with the rows moved out.
+    let mut upper = t.split_off(&50);
+    let row = t.insert(&10);
+    assert_eq!(row.get(), TEST_LENGTH + 2);
+    t.append(&mut upper);
+    t.validate().unwrap();
+    assert_eq!(t.len(), TEST_LENGTH + 2);
+    assert_eq!(t.value(row), Some(&10));
+}
+
+#[test]
+#[should_panic(expected = "values of the triees overlap")]
+fn test_append_overlap() {
+    use avltriee::Avltriee;
+
+    let mut t = Avltriee::new();
+    t.update(1.try_into().unwrap(), &10);
+    let mut other = Avltriee::new();
+    other.update(2.try_into().unwrap(), &10);
+    t.append(&mut other);
+}